@@ -5,10 +5,15 @@ use std::env::VarError;
 use std::str::FromStr;
 
 use log::error;
+use rust_decimal::Decimal;
 
 pub fn use_testnet() -> bool {
-    let e = env::var("USE_TESTNET");
-    map_to_boolean(e.unwrap().as_str())
+    let e = env::var("USE_TESTNET").unwrap_or_default();
+    map_to_boolean(e.as_str())
+}
+
+pub fn default_exchange() -> String {
+    env::var("DEFAULT_EXCHANGE").unwrap_or_else(|_| String::from("BYBIT"))
 }
 
 fn map_to_boolean(e: &str) -> bool {
@@ -19,4 +24,157 @@ fn map_to_boolean(e: &str) -> bool {
         "f" => false,
         _ => false
     }
-}
\ No newline at end of file
+}
+pub fn http_timeout_secs() -> u64 {
+    env_or("HTTP_TIMEOUT_SECS", 10)
+}
+
+pub fn http_connect_timeout_secs() -> u64 {
+    env_or("HTTP_CONNECT_TIMEOUT_SECS", 5)
+}
+
+pub fn http_keep_alive_secs() -> u64 {
+    env_or("HTTP_KEEP_ALIVE_SECS", 60)
+}
+
+pub fn http_pool_idle_timeout_secs() -> u64 {
+    env_or("HTTP_POOL_IDLE_TIMEOUT_SECS", 90)
+}
+
+pub fn http_pool_max_idle_per_host() -> usize {
+    env_or("HTTP_POOL_MAX_IDLE_PER_HOST", 8)
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    match env::var(name) {
+        Ok(value) => value.trim().parse::<T>().unwrap_or_else(|_| {
+            error!("Invalid value for {}: {}", name, value);
+            default
+        }),
+        Err(VarError::NotPresent) => default,
+        Err(e) => {
+            error!("Can not read {}: {}", name, e);
+            default
+        }
+    }
+}
+
+/// Value of `name` when it is set and valid.
+fn env_opt<T: FromStr>(name: &str) -> Option<T> {
+    let value = env::var(name).ok()?;
+    match value.trim().parse::<T>() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            error!("Invalid value for {}: {}", name, value);
+            None
+        }
+    }
+}
+
+/// CoinEx margin mode used until a signal switches it: 1 isolated, 2 cross.
+pub fn coinex_position_type() -> i8 {
+    env_or("POSITION_TYPE", 1)
+}
+
+/// Milliseconds a signed Binance request stays valid after its timestamp.
+pub fn binance_recv_window() -> u64 {
+    env_or("BINANCE_RECV_WINDOW", 5000)
+}
+
+/// Milliseconds a signed Bybit V5 request stays valid after its timestamp.
+pub fn bybit_recv_window() -> u64 {
+    env_or("BYBIT_RECV_WINDOW", 5000)
+}
+
+/// Seconds the Bybit symbol list (tick size, lot size, leverage) is cached before it is reloaded.
+pub fn symbol_info_refresh_secs() -> u64 {
+    env_or("SYMBOL_INFO_REFRESH_SECS", 3600)
+}
+
+/// Seconds between polls of a position's take profit orders.
+pub fn exit_watch_interval_secs() -> u64 {
+    env_or("EXIT_WATCH_INTERVAL_SECS", 10)
+}
+
+/// Round trip fees in percent of the entry price added to a breakeven stop.
+pub fn breakeven_fee_percent() -> Decimal {
+    env_or("BREAKEVEN_FEE_PERCENT", Decimal::new(12, 2))
+}
+
+/// Seconds a limit entry is given to fill when the signal sets no `entry_timeout`.
+pub fn entry_timeout_secs() -> u64 {
+    env_or("ENTRY_TIMEOUT_SECS", 60)
+}
+
+/// Seconds between polls of a resting limit entry.
+pub fn entry_poll_interval_secs() -> u64 {
+    env_or("ENTRY_POLL_INTERVAL_SECS", 2)
+}
+
+/// What a signal against the open position does, `OPPOSITE_SIGNAL_POLICY_<SYMBOL>` overrides
+/// `OPPOSITE_SIGNAL_POLICY` for one symbol.
+pub fn opposite_signal_policy<T: FromStr>(symbol: &str, default: T) -> T {
+    let default = env_or("OPPOSITE_SIGNAL_POLICY", default);
+    env_or(&format!("OPPOSITE_SIGNAL_POLICY_{}", symbol.to_uppercase()), default)
+}
+
+/// Largest entry value in USD, unlimited when unset.
+pub fn max_notional() -> Option<Decimal> {
+    env_opt("MAX_NOTIONAL")
+}
+
+/// Highest leverage a signal may set, the signal's own when unset.
+pub fn max_leverage() -> Option<i32> {
+    env_opt("MAX_LEVERAGE")
+}
+
+/// Most positions open at once across all symbols, unlimited when unset.
+pub fn concurrent_positions() -> Option<usize> {
+    env_opt("CONCURRENT_POSITION")
+}
+
+/// Largest value in USD of all open positions together, unlimited when unset.
+pub fn max_total_exposure() -> Option<Decimal> {
+    env_opt("MAX_TOTAL_EXPOSURE")
+}
+
+/// Largest value in USD of the position on one symbol, unlimited when unset.
+pub fn max_symbol_exposure() -> Option<Decimal> {
+    env_opt("MAX_SYMBOL_EXPOSURE")
+}
+
+/// Symbols that move together as `NAME:SYMBOL,SYMBOL;NAME:SYMBOL,...`, e.g. `BTC:BTCUSDT,BTCUSD`.
+pub fn correlation_groups() -> String {
+    env::var("CORRELATION_GROUPS").unwrap_or_default()
+}
+
+/// Most positions open at once within one correlation group, unlimited when unset.
+pub fn max_group_positions() -> Option<usize> {
+    env_opt("MAX_GROUP_POSITIONS")
+}
+
+/// Largest value in USD of the positions of one correlation group, unlimited when unset.
+pub fn max_group_exposure() -> Option<Decimal> {
+    env_opt("MAX_GROUP_EXPOSURE")
+}
+
+/// Loss in USD over the UTC day, realised and unrealised, that stops entries, unlimited when unset.
+pub fn daily_loss_limit() -> Option<Decimal> {
+    env_opt("DAILY_LOSS_LIMIT")
+}
+
+/// Losing closes in a row within the UTC day that stop entries, unlimited when unset.
+pub fn max_consecutive_losses() -> Option<usize> {
+    env_opt("MAX_CONSECUTIVE_LOSSES")
+}
+
+/// Seconds between checks of the kill switch of the accounts that sent entries, also without new signals.
+pub fn kill_switch_interval_secs() -> u64 {
+    env_or("KILL_SWITCH_INTERVAL_SECS", 60)
+}
+
+/// Whether tripping the kill switch also closes every open position.
+pub fn kill_switch_flatten() -> bool {
+    let e = env::var("KILL_SWITCH_FLATTEN").unwrap_or_default();
+    map_to_boolean(e.as_str())
+}
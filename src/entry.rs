@@ -0,0 +1,156 @@
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use rust_decimal::Decimal;
+
+use crate::common::environments::entry_poll_interval_secs;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::MarketApi;
+use crate::exchange::structs::{Order, OrderStatus, OrderType, TimeInForce};
+use crate::robot::Leg;
+
+/// How the entry order is placed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EntryMode {
+    Market,
+    /// Limit order at the signal price.
+    Limit,
+    /// Limit order at the signal price that only adds liquidity.
+    PostOnly,
+}
+
+impl std::str::FromStr for EntryMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "MARKET" => Ok(EntryMode::Market),
+            "LIMIT" => Ok(EntryMode::Limit),
+            "POST_ONLY" | "POSTONLY" => Ok(EntryMode::PostOnly),
+            _ => Err(format!("Unsupported entry mode: {}", s)),
+        }
+    }
+}
+
+impl EntryMode {
+    pub fn order_type(&self) -> OrderType {
+        match self {
+            EntryMode::Market => OrderType::Market,
+            EntryMode::Limit | EntryMode::PostOnly => OrderType::Limit,
+        }
+    }
+
+    pub fn time_in_force(&self) -> TimeInForce {
+        match self {
+            EntryMode::PostOnly => TimeInForce::PostOnly,
+            EntryMode::Market | EntryMode::Limit => TimeInForce::GoodTillCancel,
+        }
+    }
+}
+
+/// What happens to the part of a limit entry left unfilled at the timeout.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Fallback {
+    /// Cancel it, the exits cover what filled.
+    Cancel,
+    /// Cancel it and buy or sell the remainder at market.
+    Market,
+}
+
+impl std::str::FromStr for Fallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "CANCEL" => Ok(Fallback::Cancel),
+            "MARKET" => Ok(Fallback::Market),
+            _ => Err(format!("Unsupported entry fallback: {}", s)),
+        }
+    }
+}
+
+/// How a signal enters its position.
+#[derive(Copy, Clone, Debug)]
+pub struct Entry {
+    pub mode: EntryMode,
+    /// Time a limit entry is given to fill.
+    pub timeout: Duration,
+    pub fallback: Fallback,
+}
+
+/// Place the entry `order` and return the quantity it filled, `None` when the connector does not
+/// report it. A limit entry is followed until it fills or times out, then what is left is
+/// cancelled and, by the fallback, sent at market. Connectors that cannot follow orders only
+/// take market entries.
+pub async fn enter(market: &dyn MarketApi, entry: &Entry, order: Order, signal_id: &str) -> ExchangeResult<Option<Decimal>> {
+    if entry.mode != EntryMode::Market && !market.tracks_orders() {
+        return Err(ExchangeError::Unsupported(format!("{:?} entry", entry.mode)));
+    }
+    let symbol = order.symbol.clone();
+    let qty = order.qty;
+    let mut remainder = Order {
+        symbol: order.symbol.clone(),
+        time_in_force: TimeInForce::GoodTillCancel,
+        price: Decimal::ZERO,
+        qty,
+        reduce_only: order.reduce_only,
+        close_on_trigger: order.close_on_trigger,
+        order_type: OrderType::Market,
+        leverage: order.leverage,
+        side: order.side,
+        take_profit: None,
+        stop_loss: None,
+        order_link_id: Some(Leg::FallbackEntry.link_id(signal_id)),
+    };
+
+    let ack = market.order(order).await?;
+    info!("Entry placed symbol:{} mode:{:?} order_id:{} status:{:?} executed:{:?} avg_price:{:?}", &symbol, entry.mode, &ack.order_id, ack.status, ack.executed_qty, ack.avg_price);
+    if entry.mode == EntryMode::Market {
+        return Ok(ack.executed_qty.filter(|qty| *qty > Decimal::ZERO));
+    }
+
+    let interval = Duration::from_secs(entry_poll_interval_secs());
+    let deadline = Instant::now() + entry.timeout;
+    loop {
+        match market.order_status(&symbol, &ack.order_id).await {
+            Ok(order) if order.status == OrderStatus::Filled => return Ok(Some(order.executed_qty)),
+            // A post-only order that would cross is cancelled by the exchange.
+            Ok(order) if !order.status.is_open() => break,
+            Ok(_) => {}
+            Err(e) if e.is_retryable() => warn!("Follow entry interrupted symbol:{} {}", &symbol, e),
+            Err(e) => return Err(e),
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        tokio::time::sleep(interval.min(deadline - now)).await;
+    }
+
+    // The order may fill between the last poll and the cancel, the status after it is what counts.
+    let cancelled = market.cancel_order(&symbol, &ack.order_id).await;
+    let status = market.order_status(&symbol, &ack.order_id).await?;
+    if let Err(e) = cancelled {
+        if status.status.is_open() {
+            return Err(e);
+        }
+    }
+    let filled = status.executed_qty;
+    info!("Entry ended symbol:{} status:{:?} filled:{} of:{}", &symbol, status.status, filled, qty);
+
+    remainder.qty = qty - filled;
+    if entry.fallback == Fallback::Cancel || remainder.qty <= Decimal::ZERO {
+        return Ok(Some(filled));
+    }
+    info!("Send remainder at market symbol:{} qty:{}", &symbol, remainder.qty);
+    match market.order(remainder).await {
+        Ok(ack) => match ack.executed_qty.filter(|qty| *qty > Decimal::ZERO) {
+            Some(executed) => Ok(Some(filled + executed)),
+            None => Ok(None),
+        },
+        Err(e) => {
+            warn!("Remainder not filled symbol:{} {}", &symbol, e);
+            Ok(Some(filled))
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt::Formatter;
+
+use serde_json::Value;
+
+use crate::common::environments::{default_exchange, use_testnet};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Exchange {
+    Bybit,
+    Coinex,
+    Kucoin,
+    Binance,
+    Okx,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AccountType {
+    Classic,
+    Unified,
+}
+
+#[derive(Clone, Debug)]
+pub struct Credentials {
+    pub api_key: String,
+    pub api_secret: String,
+    pub passphrase: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountConfig {
+    pub exchange: Exchange,
+    pub credentials: Credentials,
+    pub account_type: AccountType,
+    pub testnet: bool,
+    /// Entry sizing of signals that set none, see `sizing::Sizing`.
+    pub sizing: Option<String>,
+}
+
+impl std::fmt::Display for Exchange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Exchange::Bybit => write!(f, "BYBIT"),
+            Exchange::Coinex => write!(f, "COINEX"),
+            Exchange::Kucoin => write!(f, "KUCOIN"),
+            Exchange::Binance => write!(f, "BINANCE"),
+            Exchange::Okx => write!(f, "OKX"),
+        }
+    }
+}
+
+impl std::str::FromStr for Exchange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "BYBIT" => Ok(Exchange::Bybit),
+            "COINEX" => Ok(Exchange::Coinex),
+            "KUCOIN" => Ok(Exchange::Kucoin),
+            "BINANCE" => Ok(Exchange::Binance),
+            "OKX" => Ok(Exchange::Okx),
+            _ => Err(format!("Unsupported exchange: {}", s)),
+        }
+    }
+}
+
+impl std::str::FromStr for AccountType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "CLASSIC" | "NORMAL" => Ok(AccountType::Classic),
+            "UNIFIED" | "UTA" => Ok(AccountType::Unified),
+            _ => Err(format!("Unsupported account type: {}", s)),
+        }
+    }
+}
+
+impl Exchange {
+    pub fn has_testnet(&self) -> bool {
+        match self {
+            Exchange::Bybit => true,
+            Exchange::Coinex => false,
+            Exchange::Kucoin => true,
+            Exchange::Binance => true,
+            Exchange::Okx => true,
+        }
+    }
+
+    pub fn requires_passphrase(&self) -> bool {
+        matches!(self, Exchange::Kucoin | Exchange::Okx)
+    }
+}
+
+impl AccountConfig {
+    /// Build the account from the decoded METADATA header.
+    ///
+    /// The exchange is taken from the signal, then `EXCHANGE`, then the `DEFAULT_EXCHANGE`
+    /// environment variable (default BYBIT), and the keys from `<EXCHANGE>_API_KEY`,
+    /// `<EXCHANGE>_API_SECRET` and `<EXCHANGE>_API_PASSPHRASE`.
+    /// `ACCOUNT_TYPE` (CLASSIC or UNIFIED, default CLASSIC) picks the Bybit API version and
+    /// `SIZING` the entry size of signals that set none.
+    /// Key names are matched case-insensitively.
+    pub fn from_metadata(metadata: &Value, signal_exchange: Option<&str>) -> Result<AccountConfig, String> {
+        let exchange = signal_exchange.map(|s| s.to_string())
+            .or_else(|| metadata_str(metadata, "EXCHANGE"))
+            .unwrap_or_else(default_exchange)
+            .parse::<Exchange>()?;
+
+        let api_key = metadata_str(metadata, &format!("{}_API_KEY", exchange))
+            .ok_or(format!("Missing {}_API_KEY in metadata", exchange))?;
+        let api_secret = metadata_str(metadata, &format!("{}_API_SECRET", exchange))
+            .ok_or(format!("Missing {}_API_SECRET in metadata", exchange))?;
+        let passphrase = metadata_str(metadata, &format!("{}_API_PASSPHRASE", exchange));
+        if passphrase.is_none() && exchange.requires_passphrase() {
+            return Err(format!("Missing {}_API_PASSPHRASE in metadata", exchange));
+        }
+
+        let account_type = match metadata_str(metadata, "ACCOUNT_TYPE") {
+            Some(account_type) => account_type.parse::<AccountType>()?,
+            None => AccountType::Classic,
+        };
+
+        let testnet = match metadata_value(metadata, "TESTNET") {
+            Some(Value::Bool(b)) => *b,
+            Some(Value::String(s)) => s.eq_ignore_ascii_case("true"),
+            _ => use_testnet(),
+        };
+        if testnet && !exchange.has_testnet() {
+            return Err(format!("{} has no testnet, set TESTNET to false", exchange));
+        }
+
+        Ok(AccountConfig {
+            exchange,
+            credentials: Credentials { api_key, api_secret, passphrase },
+            account_type,
+            testnet,
+            sizing: metadata_str(metadata, "SIZING"),
+        })
+    }
+}
+
+fn metadata_value<'a>(metadata: &'a Value, key: &str) -> Option<&'a Value> {
+    metadata.as_object()?
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v)
+}
+
+fn metadata_str(metadata: &Value, key: &str) -> Option<String> {
+    metadata_value(metadata, key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
@@ -0,0 +1,217 @@
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{id_field, number_field};
+use crate::exchange::structs::{Order, OrderAck, ExitOptions, OrderSide, OrderStatus, OrderType, PositionInformation, TimeInForce, TriggerBy};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "side")]
+    pub _side: String,
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(rename = "quantity")]
+    pub _quantity: Decimal,
+    #[serde(rename = "price", skip_serializing_if = "Option::is_none")]
+    pub _price: Option<Decimal>,
+    #[serde(rename = "timeInForce", skip_serializing_if = "Option::is_none")]
+    pub _time_in_force: Option<String>,
+    #[serde(rename = "reduceOnly", skip_serializing_if = "Option::is_none")]
+    pub _reduce_only: Option<bool>,
+    #[serde(rename = "stopPrice", skip_serializing_if = "Option::is_none")]
+    pub _stop_price: Option<Decimal>,
+    #[serde(rename = "workingType", skip_serializing_if = "Option::is_none")]
+    pub _working_type: Option<String>,
+    #[serde(rename = "newOrderRespType")]
+    pub _new_order_resp_type: String,
+    #[serde(rename = "newClientOrderId", skip_serializing_if = "Option::is_none")]
+    pub _new_client_order_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LeverageRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "leverage")]
+    pub _leverage: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarginTypeRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "marginType")]
+    pub _margin_type: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PositionRiskRequest {
+    #[serde(rename = "symbol", skip_serializing_if = "Option::is_none")]
+    pub _symbol: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelAllRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ErrorResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub msg: String,
+}
+
+impl OrderAck {
+    pub fn from_binance(value: &Value) -> ExchangeResult<OrderAck> {
+        let status = match value.get("status").and_then(|s| s.as_str()).unwrap_or_default() {
+            "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+            "FILLED" => OrderStatus::Filled,
+            "CANCELED" | "EXPIRED" => OrderStatus::Cancelled,
+            "REJECTED" => OrderStatus::Rejected,
+            _ => OrderStatus::New,
+        };
+        let avg_price = number_field(value, "avgPrice").unwrap_or_default();
+
+        Ok(OrderAck {
+            order_id: id_field(value, "orderId")?,
+            order_link_id: value.get("clientOrderId").and_then(|id| id.as_str()).unwrap_or_default().to_string(),
+            status,
+            avg_price: if avg_price > Decimal::ZERO { Some(avg_price) } else { None },
+            executed_qty: number_field(value, "executedQty").ok(),
+        })
+    }
+}
+
+impl ErrorResponse {
+    pub fn into_error(self) -> ExchangeError {
+        let code = self.code;
+        let message = self.msg;
+        match code {
+            -1021 | -1022 | -2014 | -2015 => ExchangeError::Auth { code, message },
+            -1003 | -1015 => ExchangeError::RateLimited { code, message },
+            -2018 | -2019 => ExchangeError::InsufficientBalance { code, message },
+            _ => ExchangeError::Exchange { code, message },
+        }
+    }
+}
+
+//------------- Order Request -------------
+impl OrderRequest {
+    pub fn new(order: &Order) -> Self {
+        let (price, time_in_force) = match order.order_type {
+            OrderType::Limit => (Some(order.price), Some(get_time_in_force(&order.time_in_force))),
+            OrderType::Market => (None, None),
+        };
+        OrderRequest {
+            _symbol: order.symbol.to_string(),
+            _side: get_side(&order.side),
+            _type: order.order_type.to_string().to_uppercase(),
+            _quantity: order.qty,
+            _price: price,
+            _time_in_force: time_in_force,
+            _reduce_only: order.reduce_only.filter(|r| *r),
+            _stop_price: None,
+            _working_type: None,
+            // RESULT reports the fill of market orders, the default ACK only the IDs.
+            _new_order_resp_type: String::from("RESULT"),
+            _new_client_order_id: order.order_link_id.clone(),
+        }
+    }
+
+    /// Reduce-only `STOP_MARKET` or `TAKE_PROFIT_MARKET` exit for a position opened on `side`.
+    pub fn exit(symbol: &str, side: &OrderSide, qty: Decimal, stop_price: Decimal, take_profit: bool, options: ExitOptions) -> Self {
+        OrderRequest {
+            _symbol: symbol.to_string(),
+            _side: get_side(&side.opposite()),
+            _type: String::from(if take_profit { "TAKE_PROFIT_MARKET" } else { "STOP_MARKET" }),
+            _quantity: qty,
+            _price: None,
+            _time_in_force: None,
+            _reduce_only: Some(true),
+            _stop_price: Some(stop_price),
+            _working_type: Some(String::from(match options.trigger_by {
+                Some(TriggerBy::Mark) => "MARK_PRICE",
+                _ => "CONTRACT_PRICE",
+            })),
+            _new_order_resp_type: String::from("ACK"),
+            _new_client_order_id: options.order_link_id.map(String::from),
+        }
+    }
+}
+
+impl LeverageRequest {
+    pub fn new(symbol: &str, leverage: i32) -> Self {
+        LeverageRequest {
+            _symbol: symbol.to_string(),
+            _leverage: leverage,
+        }
+    }
+}
+
+impl MarginTypeRequest {
+    pub fn new(symbol: &str, isolated: bool) -> Self {
+        MarginTypeRequest {
+            _symbol: symbol.to_string(),
+            _margin_type: String::from(if isolated { "ISOLATED" } else { "CROSSED" }),
+        }
+    }
+}
+
+impl PositionRiskRequest {
+    pub fn new(symbol: &str) -> Self {
+        PositionRiskRequest { _symbol: Some(symbol.to_string()) }
+    }
+
+    /// Every symbol of the account.
+    pub fn all() -> Self {
+        PositionRiskRequest { _symbol: None }
+    }
+}
+
+impl CancelAllRequest {
+    pub fn new(symbol: &str) -> Self {
+        CancelAllRequest { _symbol: symbol.to_string() }
+    }
+}
+
+impl PositionInformation {
+    pub fn from_binance(value: &Value) -> ExchangeResult<PositionInformation> {
+        let position_amt = number_field(value, "positionAmt")?;
+        let side = if position_amt >= Decimal::ZERO { OrderSide::Long } else { OrderSide::Short };
+
+        Ok(PositionInformation {
+            entry_price: number_field(value, "entryPrice")?,
+            free_qty: position_amt.abs(),
+            is_isolated: value.get("marginType").and_then(|m| m.as_str()) == Some("isolated"),
+            leverage: number_field(value, "leverage")?.to_i32().unwrap_or_default(),
+            liq_price: number_field(value, "liquidationPrice")?,
+            side,
+            size: position_amt.abs(),
+            symbol: value.get("symbol").and_then(|s| s.as_str()).unwrap_or_default().to_string(),
+            unrealised_pnl: number_field(value, "unRealizedProfit").unwrap_or_default(),
+        })
+    }
+}
+
+fn get_side(side: &OrderSide) -> String {
+    match side {
+        OrderSide::Short => String::from("SELL"),
+        OrderSide::Long => String::from("BUY"),
+    }
+}
+
+fn get_time_in_force(time_in_force: &TimeInForce) -> String {
+    match time_in_force {
+        TimeInForce::GoodTillCancel => String::from("GTC"),
+        TimeInForce::ImmediateOrCancel => String::from("IOC"),
+        TimeInForce::FillOrKill => String::from("FOK"),
+        TimeInForce::PostOnly => String::from("GTX"),
+    }
+}
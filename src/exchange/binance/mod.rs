@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use reqwest::header::HeaderValue;
+use ring::hmac;
+use rust_decimal::Decimal;
+use rustc_serialize::hex::ToHex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::common::environments::binance_recv_window;
+use crate::common::utils::get_current_timestamp;
+use crate::exchange::account::{AccountConfig, Credentials};
+use crate::exchange::binance::market_structs::*;
+use crate::exchange::binance::symbols::SymbolInfo;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{MarketApi, number_field};
+use crate::exchange::structs::*;
+
+mod market_structs;
+mod symbols;
+
+pub struct Market {
+    client: Client,
+    credentials: Credentials,
+    base_url: &'static str,
+}
+
+const MAIN_BASE_URL: &str = "https://fapi.binance.com";
+const BASE_URL_TESTNET: &str = "https://testnet.binancefuture.com";
+
+const ORDER_PATH: &str = "/fapi/v1/order";
+const LEVERAGE_PATH: &str = "/fapi/v1/leverage";
+const MARGIN_TYPE_PATH: &str = "/fapi/v1/marginType";
+const POSITION_RISK_PATH: &str = "/fapi/v2/positionRisk";
+const BALANCE_PATH: &str = "/fapi/v2/balance";
+const CANCEL_ALL_PATH: &str = "/fapi/v1/allOpenOrders";
+
+const MARGIN_TYPE_NOT_MODIFIED: i64 = -4046;
+
+impl Market {
+    pub fn new(account: &AccountConfig, client: Client) -> Self {
+        Market {
+            client,
+            credentials: account.credentials.clone(),
+            base_url: if account.testnet { BASE_URL_TESTNET } else { MAIN_BASE_URL },
+        }
+    }
+
+    async fn call_api<T: Serialize>(&self, request: &T, api_path: &str, method: HttpMethod) -> ExchangeResult<Value> {
+        let params = serde_urlencoded::to_string(request).map_err(|e| ExchangeError::decode(e.to_string()))?;
+        call_api(&self.client, self.base_url, params, api_path, method, &self.credentials).await
+    }
+
+    async fn symbol_info(&self, symbol: &str) -> ExchangeResult<SymbolInfo> {
+        symbols::symbol_info(&self.client, self.base_url, symbol).await
+    }
+
+    async fn exit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, stop_price: Decimal, take_profit: bool, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        let qty = match qty {
+            Some(qty) => qty,
+            None => self.position(symbol).await?.map(|p| p.size).unwrap_or_default(),
+        };
+        let info = self.symbol_info(symbol).await?;
+        let request = OrderRequest::exit(symbol, side, info.round_qty(symbol, qty)?, info.round_price(stop_price), take_profit, options);
+        self.call_api(&request, ORDER_PATH, HttpMethod::Post).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MarketApi for Market {
+    fn triggers_by(&self, trigger_by: TriggerBy) -> bool {
+        // Binance triggers on the contract or the mark price.
+        trigger_by != TriggerBy::Index
+    }
+
+    async fn order(&self, order: Order) -> ExchangeResult<OrderAck> {
+        let order = self.symbol_info(&order.symbol).await?.round_order(order)?;
+        let request = OrderRequest::new(&order);
+        let result = self.call_api(&request, ORDER_PATH, HttpMethod::Post).await?;
+        OrderAck::from_binance(&result)
+    }
+
+    async fn take_profit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, _stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        match take_profit {
+            Some(price) => self.exit(symbol, qty, side, price, true, options).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn stop_loss(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        if let Some(price) = stop_loss {
+            self.exit(symbol, qty, side, price, false, options).await?;
+        }
+        if let Some(price) = take_profit {
+            self.exit(symbol, qty, side, price, true, ExitOptions { order_link_id: None, ..options }).await?;
+        }
+        Ok(())
+    }
+
+    async fn position(&self, symbol: &str) -> ExchangeResult<Option<PositionInformation>> {
+        let request = PositionRiskRequest::new(symbol);
+        let result = self.call_api(&request, POSITION_RISK_PATH, HttpMethod::Get).await?;
+        let positions = result.as_array().cloned().unwrap_or_default();
+        // In hedge mode both sides are listed, prefer the one holding a position.
+        let position = positions.iter()
+            .find(|p| number_field(p, "positionAmt").map(|amt| amt != Decimal::ZERO).unwrap_or(false))
+            .or_else(|| positions.first());
+        match position {
+            Some(value) => Ok(Some(PositionInformation::from_binance(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn positions(&self) -> ExchangeResult<Vec<PositionInformation>> {
+        let result = self.call_api(&PositionRiskRequest::all(), POSITION_RISK_PATH, HttpMethod::Get).await?;
+        result.as_array().map(|list| list.as_slice()).unwrap_or_default()
+            .iter()
+            .filter(|p| number_field(p, "positionAmt").map(|amt| amt != Decimal::ZERO).unwrap_or(false))
+            .map(PositionInformation::from_binance)
+            .collect()
+    }
+
+    async fn is_in_position(&self, symbol: &str) -> ExchangeResult<bool> {
+        Ok(match self.position(symbol).await? {
+            Some(position_info) => position_info.entry_price > Decimal::ZERO,
+            None => false,
+        })
+    }
+
+    async fn wallet_available_balance(&self, coin: &str) -> ExchangeResult<Decimal> {
+        let result = call_api(&self.client, self.base_url, String::new(), BALANCE_PATH, HttpMethod::Get, &self.credentials).await?;
+        let balance = result.as_array()
+            .and_then(|assets| assets.iter().find(|a| a.get("asset").and_then(|c| c.as_str()) == Some(coin)));
+        match balance {
+            Some(asset) => number_field(asset, "availableBalance"),
+            None => Ok(Decimal::ZERO),
+        }
+    }
+
+    async fn leverage(&self, symbol: &str, leverage: i32) -> ExchangeResult<()> {
+        let request = LeverageRequest::new(symbol, leverage);
+        self.call_api(&request, LEVERAGE_PATH, HttpMethod::Post).await?;
+        Ok(())
+    }
+
+    async fn switch_isolated(&self, symbol: &str, isolated: bool, _leverage: i32) -> ExchangeResult<()> {
+        let request = MarginTypeRequest::new(symbol, isolated);
+        match self.call_api(&request, MARGIN_TYPE_PATH, HttpMethod::Post).await {
+            Err(ExchangeError::Exchange { code: MARGIN_TYPE_NOT_MODIFIED, .. }) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    async fn cancel_all(&self, symbol: &str) -> ExchangeResult<()> {
+        self.call_api(&CancelAllRequest::new(symbol), CANCEL_ALL_PATH, HttpMethod::Delete).await?;
+        Ok(())
+    }
+
+    async fn qty_step(&self, symbol: &str) -> ExchangeResult<Option<Decimal>> {
+        Ok(Some(self.symbol_info(symbol).await?.qty_step))
+    }
+}
+
+async fn call_api(client: &Client, base_url: &str, params: String, api_path: &str, method: HttpMethod, credentials: &Credentials) -> ExchangeResult<Value> {
+    let mut query = params;
+    if !query.is_empty() {
+        query.push('&');
+    }
+    query.push_str(&format!("recvWindow={}&timestamp={}", binance_recv_window(), get_current_timestamp()));
+
+    // Create sign key and sign query
+    let key = hmac::Key::new(hmac::HMAC_SHA256, credentials.api_secret.as_bytes());
+    let signature = hmac::sign(&key, query.as_bytes()).as_ref().to_hex();
+
+    let url = format!("{}{}?{}&signature={}", base_url, api_path, query, signature);
+    let api_key = HeaderValue::from_str(&credentials.api_key).map_err(|e| ExchangeError::Auth { code: -1, message: e.to_string() })?;
+
+    // Send request
+    let resp = match method {
+        HttpMethod::Get => client.get(url),
+        HttpMethod::Post => client.post(url),
+        HttpMethod::Delete => client.delete(url),
+        HttpMethod::Put => client.put(url),
+    }.header("X-MBX-APIKEY", api_key).send().await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if status.as_u16() == 429 || status.as_u16() == 418 {
+        return Err(ExchangeError::RateLimited { code: status.as_u16() as i64, message: body });
+    }
+    if !status.is_success() {
+        return Err(match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(error) => error.into_error(),
+            Err(_) => ExchangeError::HttpStatus { status: status.as_u16(), body },
+        });
+    }
+    Ok(serde_json::from_str::<Value>(&body)?)
+}
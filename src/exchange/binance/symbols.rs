@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use log::info;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+use crate::common::environments::symbol_info_refresh_secs;
+use crate::exchange::binance::market_structs::ErrorResponse;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{number_field, round_to_step};
+use crate::exchange::structs::{Order, OrderType};
+
+const EXCHANGE_INFO_PATH: &str = "/fapi/v1/exchangeInfo";
+
+/// A symbol missing from a list loaded this recently is unknown, without loading the list again.
+const UNKNOWN_SYMBOL_RELOAD: Duration = Duration::from_secs(60);
+
+/// Trading rules of one symbol.
+#[derive(Clone, Debug)]
+pub struct SymbolInfo {
+    pub tick_size: Decimal,
+    pub qty_step: Decimal,
+    pub min_qty: Decimal,
+    pub max_qty: Decimal,
+    pub min_notional: Decimal,
+}
+
+/// Symbols loaded from one base URL, testnet and mainnet list different symbols.
+struct Symbols {
+    loaded_at: Instant,
+    symbols: HashMap<String, SymbolInfo>,
+}
+
+static CACHE: OnceLock<RwLock<HashMap<&'static str, Symbols>>> = OnceLock::new();
+
+fn cache() -> &'static RwLock<HashMap<&'static str, Symbols>> {
+    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Trading rules of `symbol`, the list is reloaded once it is older than the refresh interval or
+/// does not know the symbol yet. A symbol still missing is reported unknown for a minute before
+/// the list is loaded again.
+pub async fn symbol_info(client: &Client, base_url: &'static str, symbol: &str) -> ExchangeResult<SymbolInfo> {
+    let unknown = || ExchangeError::InvalidOrder(format!("Unknown symbol {}", symbol));
+    let refresh = Duration::from_secs(symbol_info_refresh_secs());
+    if let Some(symbols) = cache().read().unwrap().get(base_url) {
+        if symbols.loaded_at.elapsed() < refresh {
+            match symbols.symbols.get(symbol) {
+                Some(info) => return Ok(info.clone()),
+                None if symbols.loaded_at.elapsed() < UNKNOWN_SYMBOL_RELOAD => return Err(unknown()),
+                None => {}
+            }
+        }
+    }
+
+    let symbols = load(client, base_url).await?;
+    info!("Loaded {} symbols from {}", symbols.len(), base_url);
+    let info = symbols.get(symbol).cloned();
+    cache().write().unwrap().insert(base_url, Symbols { loaded_at: Instant::now(), symbols });
+    info.ok_or_else(unknown)
+}
+
+async fn load(client: &Client, base_url: &str) -> ExchangeResult<HashMap<String, SymbolInfo>> {
+    let resp = client.get(format!("{}{}", base_url, EXCHANGE_INFO_PATH)).send().await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if !status.is_success() {
+        return Err(match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(error) => error.into_error(),
+            Err(_) => ExchangeError::HttpStatus { status: status.as_u16(), body },
+        });
+    }
+    let result = serde_json::from_str::<Value>(&body)?;
+    result.get("symbols").and_then(|symbols| symbols.as_array()).map(|list| list.as_slice()).unwrap_or_default()
+        .iter()
+        .map(|symbol| {
+            let id = symbol.get("symbol").and_then(|id| id.as_str()).ok_or_else(|| ExchangeError::decode("symbol not found"))?;
+            Ok((id.to_string(), SymbolInfo::from_value(symbol)?))
+        })
+        .collect()
+}
+
+/// The filter of type `filter_type` in a symbol's `filters`.
+fn filter<'a>(value: &'a Value, filter_type: &str) -> ExchangeResult<&'a Value> {
+    value.get("filters").and_then(|filters| filters.as_array())
+        .and_then(|filters| filters.iter().find(|f| f.get("filterType").and_then(|t| t.as_str()) == Some(filter_type)))
+        .ok_or_else(|| ExchangeError::decode(format!("{} not found", filter_type)))
+}
+
+impl SymbolInfo {
+    fn from_value(value: &Value) -> ExchangeResult<SymbolInfo> {
+        let price_filter = filter(value, "PRICE_FILTER")?;
+        let lot_size = filter(value, "LOT_SIZE")?;
+        Ok(SymbolInfo {
+            tick_size: number_field(price_filter, "tickSize")?,
+            qty_step: number_field(lot_size, "stepSize")?,
+            min_qty: number_field(lot_size, "minQty")?,
+            max_qty: number_field(lot_size, "maxQty")?,
+            min_notional: filter(value, "MIN_NOTIONAL").and_then(|f| number_field(f, "notional")).unwrap_or_default(),
+        })
+    }
+
+    /// Nearest valid price.
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        round_to_step(price, self.tick_size, Decimal::round)
+    }
+
+    /// Quantity rounded down to the lot step and capped at the maximum, below the minimum is an error.
+    pub fn round_qty(&self, symbol: &str, qty: Decimal) -> ExchangeResult<Decimal> {
+        let rounded = round_to_step(qty.min(self.max_qty), self.qty_step, Decimal::floor);
+        if rounded < self.min_qty {
+            return Err(ExchangeError::InvalidOrder(format!("Order qty {} for {} is below the minimum {}", qty, symbol, self.min_qty)));
+        }
+        Ok(rounded)
+    }
+
+    /// `order` with its price and quantity on the symbol's grid. A limit order opening a position
+    /// below the minimum value is an error, reduce-only orders are exempt.
+    pub fn round_order(&self, mut order: Order) -> ExchangeResult<Order> {
+        order.qty = self.round_qty(&order.symbol, order.qty)?;
+        if order.order_type == OrderType::Limit {
+            order.price = self.round_price(order.price);
+            let notional = order.price * order.qty;
+            if order.reduce_only != Some(true) && notional < self.min_notional {
+                return Err(ExchangeError::InvalidOrder(format!("Order value {} for {} is below the minimum {}", notional, order.symbol, self.min_notional)));
+            }
+        }
+        Ok(order)
+    }
+}
@@ -1,20 +1,24 @@
 use std::collections::HashMap;
 use std::fmt::Formatter;
 
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::common::utils::get_current_timestamp;
-use crate::exchange::structs::{Order, OrderSide, OrderType, PositionInformation, TimeInForce};
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::number_field;
+use crate::exchange::structs::{ContractType, Order, OrderInfo, OrderSide, OrderStatus, OrderType, PositionInformation, TimeInForce, TrailingStop, TriggerBy};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct OrderRequest {
     #[serde(rename = "order_type")]
     pub _order_type: String,
     #[serde(rename = "price")]
-    pub _price: f64,
+    pub _price: Decimal,
     #[serde(rename = "qty")]
-    pub _qty: f64,
+    pub _qty: Decimal,
     #[serde(rename = "symbol")]
     pub _symbol: String,
     #[serde(rename = "side")]
@@ -22,13 +26,15 @@ pub struct OrderRequest {
     #[serde(rename = "time_in_force")]
     pub _time_in_force: String,
     #[serde(rename = "take_profit")]
-    pub _take_profit: Option<f64>,
+    pub _take_profit: Option<Decimal>,
     #[serde(rename = "stop_loss")]
-    pub _stop_loss: Option<f64>,
+    pub _stop_loss: Option<Decimal>,
     #[serde(rename = "reduce_only")]
     pub _reduce_only: Option<bool>,
     #[serde(rename = "close_on_trigger")]
     pub _close_on_trigger: Option<bool>,
+    #[serde(rename = "order_link_id")]
+    pub _order_link_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -38,9 +44,17 @@ pub struct TradingStop {
     #[serde(rename = "side")]
     pub _side: String,
     #[serde(rename = "take_profit")]
-    pub _take_profit: Option<f64>,
+    pub _take_profit: Option<Decimal>,
     #[serde(rename = "stop_loss")]
-    pub _stop_loss: Option<f64>,
+    pub _stop_loss: Option<Decimal>,
+    #[serde(rename = "tp_trigger_by")]
+    pub _tp_trigger_by: Option<String>,
+    #[serde(rename = "sl_trigger_by")]
+    pub _sl_trigger_by: Option<String>,
+    #[serde(rename = "trailing_stop")]
+    pub _trailing_stop: Option<Decimal>,
+    #[serde(rename = "new_trailing_active")]
+    pub _new_trailing_active: Option<Decimal>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -66,32 +80,101 @@ pub struct LeverageRequest {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct PositionRequest {
+pub struct InverseLeverageRequest {
     #[serde(rename = "symbol")]
     pub _symbol: String,
+    #[serde(rename = "leverage")]
+    pub _leverage: i32,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct WalletInformation {
-    #[serde(rename = "coin")]
-    pub _coin: String,
+pub struct OrderIdRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "order_id")]
+    pub _order_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderLinkRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "order_link_id")]
+    pub _order_link_id: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct ContractRequest {
+pub struct CancelAllRequest {
     #[serde(rename = "symbol")]
     pub _symbol: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClosedPnlRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "start_time")]
+    pub _start_time: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReplaceOrderRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "order_id")]
+    pub _order_id: String,
+    #[serde(rename = "p_r_price")]
+    pub _price: Option<Decimal>,
+    #[serde(rename = "p_r_qty")]
+    pub _qty: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderListRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "order_status")]
+    pub _order_status: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PositionRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WalletInformation {
+    #[serde(rename = "coin")]
+    pub _coin: String,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ApiResponse {
     pub ret_code: i64,
     pub ret_msg: String,
-    pub ext_code: String,
-    pub ext_info: String,
+    #[serde(default)]
+    pub ext_code: Option<String>,
+    #[serde(default)]
+    pub ext_info: Option<String>,
+    #[serde(default)]
     pub result: Value,
 }
 
+impl ApiResponse {
+    pub fn into_result(self) -> ExchangeResult<Value> {
+        let code = self.ret_code;
+        let message = self.ret_msg;
+        match code {
+            0 => Ok(self.result),
+            10003 | 10004 | 10005 | 10007 | 33004 => Err(ExchangeError::Auth { code, message }),
+            10006 | 10018 => Err(ExchangeError::RateLimited { code, message }),
+            30031 | 30049 | 130021 | 130052 => Err(ExchangeError::InsufficientBalance { code, message }),
+            _ => Err(ExchangeError::Exchange { code, message }),
+        }
+    }
+}
+
 impl std::fmt::Display for TimeInForce {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -103,6 +186,16 @@ impl std::fmt::Display for TimeInForce {
     }
 }
 
+impl std::fmt::Display for TriggerBy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerBy::Last => write!(f, "LastPrice"),
+            TriggerBy::Mark => write!(f, "MarkPrice"),
+            TriggerBy::Index => write!(f, "IndexPrice"),
+        }
+    }
+}
+
 impl std::fmt::Display for OrderSide {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -134,6 +227,7 @@ impl OrderRequest {
             _stop_loss: order.stop_loss,
             _reduce_only: order.reduce_only,
             _close_on_trigger: order.close_on_trigger,
+            _order_link_id: order.order_link_id,
         }
     }
 
@@ -152,42 +246,51 @@ impl OrderRequest {
         if self._order_type.eq("Limit") {
             query_map.insert(String::from("price"), Value::from(self._price.to_string()));
         }
-        if self._take_profit.is_some() {
-            query_map.insert(String::from("take_profit"), Value::from(self._take_profit.unwrap()));
+        if let Some(take_profit) = self._take_profit {
+            query_map.insert(String::from("take_profit"), Value::from(take_profit.to_string()));
+        }
+        if let Some(stop_loss) = self._stop_loss {
+            query_map.insert(String::from("stop_loss"), Value::from(stop_loss.to_string()));
         }
-        if self._stop_loss.is_some() {
-            query_map.insert(String::from("stop_loss"), Value::from(self._stop_loss.unwrap()));
+        if let Some(reduce_only) = self._reduce_only {
+            query_map.insert(String::from("reduce_only"), Value::from(reduce_only));
         }
-        if self._reduce_only.is_some() {
-            query_map.insert(String::from("reduce_only"), Value::from(self._reduce_only.unwrap()));
+        if let Some(close_on_trigger) = self._close_on_trigger {
+            query_map.insert(String::from("close_on_trigger"), Value::from(close_on_trigger));
         }
-        if self._close_on_trigger.is_some() {
-            query_map.insert(String::from("close_on_trigger"), Value::from(self._close_on_trigger.unwrap()));
+        if let Some(order_link_id) = &self._order_link_id {
+            query_map.insert(String::from("order_link_id"), Value::from(order_link_id.to_string()));
         }
 
-        return query_map;
+        query_map
     }
 }
 
 impl PositionRequest {
-    pub fn new(symbol: &String) -> Self {
-        PositionRequest { _symbol: symbol.to_string() }
+    pub fn new(symbol: &str) -> Self {
+        PositionRequest { _symbol: Some(symbol.to_string()) }
+    }
+
+    /// Positions on every symbol.
+    pub fn all() -> Self {
+        PositionRequest { _symbol: None }
     }
 
     pub fn get_query_map(&self, api_key: String) -> HashMap<String, Value> {
         //Initial data
         let timestamp = get_current_timestamp();
-        let symbol = &self._symbol;
         let mut query_map = HashMap::new();
         query_map.insert(String::from("api_key"), Value::from(api_key));
-        query_map.insert(String::from("symbol"), Value::from(symbol.to_string()));
+        if let Some(symbol) = &self._symbol {
+            query_map.insert(String::from("symbol"), Value::from(symbol.to_string()));
+        }
         query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
-        return query_map;
+        query_map
     }
 }
 
 impl WalletInformation {
-    pub fn new(coin: &String) -> Self {
+    pub fn new(coin: &str) -> Self {
         WalletInformation { _coin: coin.to_string() }
     }
 
@@ -199,12 +302,12 @@ impl WalletInformation {
         query_map.insert(String::from("api_key"), Value::from(api_key));
         query_map.insert(String::from("coin"), Value::from(coin.to_string()));
         query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
-        return query_map;
+        query_map
     }
 }
 
 impl SwitchIsolatedRequest {
-    pub fn new(symbol: &String, is_isolated: bool, buy_leverage: i32, sell_leverage: i32) -> Self {
+    pub fn new(symbol: &str, is_isolated: bool, buy_leverage: i32, sell_leverage: i32) -> Self {
         SwitchIsolatedRequest {
             _symbol: symbol.to_string(),
             _is_isolated: is_isolated,
@@ -223,12 +326,12 @@ impl SwitchIsolatedRequest {
         query_map.insert(String::from("is_isolated"), Value::from(self._is_isolated.to_string()));
         query_map.insert(String::from("buy_leverage"), Value::from(self._buy_leverage.to_string()));
         query_map.insert(String::from("sell_leverage"), Value::from(self._sell_leverage.to_string()));
-        return query_map;
+        query_map
     }
 }
 
 impl LeverageRequest {
-    pub fn new(symbol: &String, buy_leverage: i32, sell_leverage: i32) -> Self {
+    pub fn new(symbol: &str, buy_leverage: i32, sell_leverage: i32) -> Self {
         LeverageRequest {
             _symbol: symbol.to_string(),
             _buy_leverage: buy_leverage,
@@ -245,34 +348,249 @@ impl LeverageRequest {
         query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
         query_map.insert(String::from("buy_leverage"), Value::from(self._buy_leverage.to_string()));
         query_map.insert(String::from("sell_leverage"), Value::from(self._sell_leverage.to_string()));
-        return query_map;
+        query_map
+    }
+}
+
+impl InverseLeverageRequest {
+    pub fn new(symbol: &str, leverage: i32) -> Self {
+        InverseLeverageRequest {
+            _symbol: symbol.to_string(),
+            _leverage: leverage,
+        }
+    }
+
+    pub fn get_query_map(&self, api_key: String) -> HashMap<String, Value> {
+        //Initial data
+        let timestamp = get_current_timestamp();
+        let mut query_map = HashMap::new();
+        query_map.insert(String::from("api_key"), Value::from(api_key));
+        query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
+        query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
+        query_map.insert(String::from("leverage"), Value::from(self._leverage.to_string()));
+        query_map
+    }
+}
+
+impl OrderIdRequest {
+    pub fn new(symbol: &str, order_id: &str) -> Self {
+        OrderIdRequest {
+            _symbol: symbol.to_string(),
+            _order_id: order_id.to_string(),
+        }
+    }
+
+    pub fn get_query_map(&self, api_key: String) -> HashMap<String, Value> {
+        //Initial data
+        let timestamp = get_current_timestamp();
+        let mut query_map = HashMap::new();
+        query_map.insert(String::from("api_key"), Value::from(api_key));
+        query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
+        query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
+        query_map.insert(String::from("order_id"), Value::from(self._order_id.to_string()));
+        query_map
+    }
+}
+
+impl OrderLinkRequest {
+    pub fn new(symbol: &str, order_link_id: &str) -> Self {
+        OrderLinkRequest {
+            _symbol: symbol.to_string(),
+            _order_link_id: order_link_id.to_string(),
+        }
+    }
+
+    pub fn get_query_map(&self, api_key: String) -> HashMap<String, Value> {
+        //Initial data
+        let timestamp = get_current_timestamp();
+        let mut query_map = HashMap::new();
+        query_map.insert(String::from("api_key"), Value::from(api_key));
+        query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
+        query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
+        query_map.insert(String::from("order_link_id"), Value::from(self._order_link_id.to_string()));
+        query_map
+    }
+}
+
+impl CancelAllRequest {
+    pub fn new(symbol: &str) -> Self {
+        CancelAllRequest { _symbol: symbol.to_string() }
+    }
+
+    pub fn get_query_map(&self, api_key: String) -> HashMap<String, Value> {
+        //Initial data
+        let timestamp = get_current_timestamp();
+        let mut query_map = HashMap::new();
+        query_map.insert(String::from("api_key"), Value::from(api_key));
+        query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
+        query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
+        query_map
+    }
+}
+
+impl ClosedPnlRequest {
+    /// Positions closed on `symbol` since `start_time` in milliseconds.
+    pub fn new(symbol: &str, start_time: i64) -> Self {
+        ClosedPnlRequest { _symbol: symbol.to_string(), _start_time: start_time }
+    }
+
+    pub fn get_query_map(&self, api_key: String) -> HashMap<String, Value> {
+        //Initial data
+        let timestamp = get_current_timestamp();
+        let mut query_map = HashMap::new();
+        query_map.insert(String::from("api_key"), Value::from(api_key));
+        query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
+        query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
+        // The list takes seconds.
+        query_map.insert(String::from("start_time"), Value::from((self._start_time / 1000).to_string()));
+        query_map.insert(String::from("limit"), Value::from(String::from("50")));
+        query_map
+    }
+}
+
+impl ReplaceOrderRequest {
+    pub fn new(symbol: &str, order_id: &str, price: Option<Decimal>, qty: Option<Decimal>) -> Self {
+        ReplaceOrderRequest {
+            _symbol: symbol.to_string(),
+            _order_id: order_id.to_string(),
+            _price: price,
+            _qty: qty,
+        }
+    }
+
+    pub fn get_query_map(&self, api_key: String) -> HashMap<String, Value> {
+        //Initial data
+        let timestamp = get_current_timestamp();
+        let mut query_map = HashMap::new();
+        query_map.insert(String::from("api_key"), Value::from(api_key));
+        query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
+        query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
+        query_map.insert(String::from("order_id"), Value::from(self._order_id.to_string()));
+
+        if let Some(price) = self._price {
+            query_map.insert(String::from("p_r_price"), Value::from(price.to_string()));
+        }
+        if let Some(qty) = self._qty {
+            query_map.insert(String::from("p_r_qty"), Value::from(qty.to_string()));
+        }
+        query_map
+    }
+}
+
+impl OrderListRequest {
+    /// Orders on `symbol` that can still fill.
+    pub fn open(symbol: &str) -> Self {
+        OrderListRequest {
+            _symbol: symbol.to_string(),
+            _order_status: String::from("Created,New,PartiallyFilled"),
+        }
+    }
+
+    pub fn get_query_map(&self, api_key: String) -> HashMap<String, Value> {
+        //Initial data
+        let timestamp = get_current_timestamp();
+        let mut query_map = HashMap::new();
+        query_map.insert(String::from("api_key"), Value::from(api_key));
+        query_map.insert(String::from("timestamp"), Value::from(timestamp.to_string()));
+        query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
+        query_map.insert(String::from("order_status"), Value::from(self._order_status.to_string()));
+        query_map
+    }
+}
+
+/// Order status names shared by the legacy and V5 APIs.
+pub fn parse_order_status(status: &str) -> ExchangeResult<OrderStatus> {
+    match status {
+        "Created" | "New" | "PendingCancel" => Ok(OrderStatus::New),
+        "PartiallyFilled" => Ok(OrderStatus::PartiallyFilled),
+        "Filled" => Ok(OrderStatus::Filled),
+        "Cancelled" | "PartiallyFilledCanceled" | "Deactivated" => Ok(OrderStatus::Cancelled),
+        "Rejected" => Ok(OrderStatus::Rejected),
+        "Untriggered" | "Active" => Ok(OrderStatus::Untriggered),
+        "Triggered" => Ok(OrderStatus::Triggered),
+        _ => Err(ExchangeError::decode(format!("Unknown order status {}", status))),
+    }
+}
+
+impl OrderInfo {
+    pub fn from_value(value: &Value, contract_type: ContractType) -> ExchangeResult<OrderInfo> {
+        let side = if field(value, "side")?.as_str() == Some("Buy") { OrderSide::Long } else { OrderSide::Short };
+        let order_type = if field(value, "order_type")?.as_str() == Some("Limit") { OrderType::Limit } else { OrderType::Market };
+        let executed_qty = number_field(value, "cum_exec_qty").unwrap_or_default();
+        let executed_value = number_field(value, "cum_exec_value").unwrap_or_default();
+        // Inverse values are in coins for a quantity in USD.
+        let avg_price = match (executed_qty > Decimal::ZERO && executed_value > Decimal::ZERO, contract_type.is_inverse()) {
+            (false, _) => Decimal::ZERO,
+            (true, false) => executed_value / executed_qty,
+            (true, true) => executed_qty / executed_value,
+        };
+
+        Ok(OrderInfo {
+            order_id: field(value, "order_id")?.as_str().unwrap_or_default().to_string(),
+            order_link_id: value.get("order_link_id").and_then(|id| id.as_str()).unwrap_or_default().to_string(),
+            symbol: field(value, "symbol")?.as_str().unwrap_or_default().to_string(),
+            side,
+            order_type,
+            status: parse_order_status(field(value, "order_status")?.as_str().unwrap_or_default())?,
+            price: number_field(value, "price")?,
+            qty: number_field(value, "qty")?,
+            executed_qty,
+            avg_price,
+            trigger_price: None,
+            reduce_only: value.get("reduce_only").and_then(|r| r.as_bool()).unwrap_or(false),
+        })
     }
 }
 
 impl PositionInformation {
-    pub fn from_value(value: &Value) -> PositionInformation {
-        let side = if value["side"].as_str().unwrap().eq("Buy") { OrderSide::Long } else { OrderSide::Short };
-
-        PositionInformation {
-            entry_price: value.get("entry_price").unwrap().as_f64().unwrap(),
-            free_qty: value.get("free_qty").unwrap().as_f64().unwrap(),
-            is_isolated: value.get("is_isolated").unwrap().as_bool().unwrap(),
-            leverage: value.get("leverage").unwrap().as_i64().unwrap() as i32,
-            liq_price: value.get("liq_price").unwrap().as_f64().unwrap(),
+    pub fn from_value(value: &Value) -> ExchangeResult<PositionInformation> {
+        let side = if field(value, "side")?.as_str() == Some("Buy") { OrderSide::Long } else { OrderSide::Short };
+
+        // Inverse endpoints send numbers as strings and have no free_qty.
+        let size = number_field(value, "size")?;
+
+        Ok(PositionInformation {
+            entry_price: number_field(value, "entry_price")?,
+            free_qty: number_field(value, "free_qty").unwrap_or(size),
+            is_isolated: field(value, "is_isolated")?.as_bool().ok_or_else(|| ExchangeError::decode("is_isolated is not a bool"))?,
+            leverage: number_field(value, "leverage")?.to_i32().unwrap_or_default(),
+            liq_price: number_field(value, "liq_price")?,
             side,
-            size: value.get("size").unwrap().as_f64().unwrap(),
-            symbol: value.get("symbol").unwrap().to_string(),
-        }
+            size,
+            symbol: field(value, "symbol")?.as_str().unwrap_or_default().to_string(),
+            unrealised_pnl: number_field(value, "unrealised_pnl").unwrap_or_default(),
+        })
     }
 }
 
+fn field<'a>(value: &'a Value, name: &str) -> ExchangeResult<&'a Value> {
+    value.get(name).ok_or_else(|| ExchangeError::decode(format!("{} not found", name)))
+}
+
 impl TradingStop {
-    pub fn new(symbol: &String, side: &OrderSide, take_profit: Option<f64>, stop_loss: Option<f64>) -> Self {
+    pub fn new(symbol: &str, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, trigger_by: TriggerBy) -> Self {
         TradingStop {
             _symbol: symbol.to_string(),
             _side: side.to_string(),
             _take_profit: take_profit,
             _stop_loss: stop_loss,
+            _tp_trigger_by: take_profit.map(|_| trigger_by.to_string()),
+            _sl_trigger_by: stop_loss.map(|_| trigger_by.to_string()),
+            _trailing_stop: None,
+            _new_trailing_active: None,
+        }
+    }
+
+    pub fn trailing(symbol: &str, side: &OrderSide, trailing_stop: &TrailingStop) -> Self {
+        TradingStop {
+            _symbol: symbol.to_string(),
+            _side: side.to_string(),
+            _take_profit: None,
+            _stop_loss: None,
+            _tp_trigger_by: None,
+            _sl_trigger_by: None,
+            _trailing_stop: Some(trailing_stop.distance),
+            _new_trailing_active: trailing_stop.active_price,
         }
     }
 
@@ -286,12 +604,24 @@ impl TradingStop {
         query_map.insert(String::from("symbol"), Value::from(self._symbol.to_string()));
         query_map.insert(String::from("side"), Value::from(self._side.to_string()));
 
-        if self._take_profit.is_some() {
-            query_map.insert(String::from("take_profit"), Value::from(self._take_profit.unwrap()));
+        if let Some(take_profit) = self._take_profit {
+            query_map.insert(String::from("take_profit"), Value::from(take_profit.to_string()));
+        }
+        if let Some(stop_loss) = self._stop_loss {
+            query_map.insert(String::from("stop_loss"), Value::from(stop_loss.to_string()));
+        }
+        if let Some(tp_trigger_by) = &self._tp_trigger_by {
+            query_map.insert(String::from("tp_trigger_by"), Value::from(tp_trigger_by.to_string()));
+        }
+        if let Some(sl_trigger_by) = &self._sl_trigger_by {
+            query_map.insert(String::from("sl_trigger_by"), Value::from(sl_trigger_by.to_string()));
+        }
+        if let Some(trailing_stop) = self._trailing_stop {
+            query_map.insert(String::from("trailing_stop"), Value::from(trailing_stop.to_string()));
         }
-        if self._stop_loss.is_some() {
-            query_map.insert(String::from("stop_loss"), Value::from(self._stop_loss.unwrap()));
+        if let Some(new_trailing_active) = self._new_trailing_active {
+            query_map.insert(String::from("new_trailing_active"), Value::from(new_trailing_active.to_string()));
         }
-        return query_map;
+        query_map
     }
 }
\ No newline at end of file
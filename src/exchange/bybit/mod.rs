@@ -1,47 +1,153 @@
 use std::collections::HashMap;
 use std::string::String;
 
-use reqwest::header::{CONTENT_TYPE, HeaderMap};
+use async_trait::async_trait;
+use reqwest::Client;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use ring::hmac;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use rustc_serialize::hex::ToHex;
 use serde_json::Value;
 
-use crate::common::environments::use_testnet;
-use crate::exchange::bybit::market_structs::{ApiResponse, LeverageRequest, OrderRequest, PositionRequest, SwitchIsolatedRequest, TradingStop, WalletInformation};
-use crate::exchange::general::MarketApi;
+use crate::exchange::account::{AccountConfig, Credentials};
+use crate::exchange::bybit::market_structs::{ApiResponse, CancelAllRequest, ClosedPnlRequest, InverseLeverageRequest, LeverageRequest, OrderIdRequest, OrderLinkRequest, OrderListRequest, OrderRequest, PositionRequest, ReplaceOrderRequest, SwitchIsolatedRequest, TradingStop, WalletInformation};
+use crate::exchange::bybit::symbols::{SymbolInfo, SymbolList};
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{MarketApi, number_field};
 use crate::exchange::structs::*;
-use crate::exchange::structs::HttpMethod::POST;
 
 mod market_structs;
-
-pub struct Market;
+pub mod spot;
+mod symbols;
+pub mod v5;
+
+pub struct Market {
+    client: Client,
+    credentials: Credentials,
+    base_url: &'static str,
+}
 
 const MAIN_BASE_URL: &str = "https://api.bybit.com";
 const BASE_URL_TESTNET: &str = "https://api-testnet.bybit.com";
 
-const ORDER_PATH: &str = "private/linear/order/create";
-const POSITION_LIST_PATH: &str = "private/linear/position/list";
 const WALLET_BALANCE_PATH: &str = "v2/private/wallet/balance";
-const SWITCH_ISOLATED_PATH: &str = "private/linear/position/switch-isolated";
-const LEVERAGE_PATH: &str = "private/linear/position/set-leverage";
-const TRADING_STOP_PATH: &str = "private/linear/position/trading-stop";
 
-impl MarketApi for Market {
-    fn order(_order: Order, metadata: &Value) -> bool {
-        let api_key = metadata["BYBIT_API_KEY"].as_str().unwrap().to_string();
-        let api_secret = metadata["BYBIT_API_SECRET"].as_str().unwrap().to_string();
-
-        let order_request = OrderRequest::new(_order);
-        let query_params = order_request.get_query_map(api_key);
-        let response = call_api(query_params, ORDER_PATH, HttpMethod::POST, api_secret);
-        if response.ret_code != 0 {
-            println!("Error: {}:{}", response.ret_code, response.ret_msg);
+/// Private endpoints of one contract type.
+struct Endpoints {
+    order: &'static str,
+    position_list: &'static str,
+    switch_isolated: &'static str,
+    leverage: &'static str,
+    trading_stop: &'static str,
+    cancel: &'static str,
+    cancel_all: &'static str,
+    replace: &'static str,
+    search: &'static str,
+    list: &'static str,
+    closed_pnl: &'static str,
+}
+
+const LINEAR: Endpoints = Endpoints {
+    order: "private/linear/order/create",
+    position_list: "private/linear/position/list",
+    switch_isolated: "private/linear/position/switch-isolated",
+    leverage: "private/linear/position/set-leverage",
+    trading_stop: "private/linear/position/trading-stop",
+    cancel: "private/linear/order/cancel",
+    cancel_all: "private/linear/order/cancel-all",
+    replace: "private/linear/order/replace",
+    search: "private/linear/order/search",
+    list: "private/linear/order/list",
+    closed_pnl: "private/linear/trade/closed-pnl/list",
+};
+
+const INVERSE_PERPETUAL: Endpoints = Endpoints {
+    order: "v2/private/order/create",
+    position_list: "v2/private/position/list",
+    switch_isolated: "v2/private/position/switch-isolated",
+    leverage: "v2/private/position/leverage/save",
+    trading_stop: "v2/private/position/trading-stop",
+    cancel: "v2/private/order/cancel",
+    cancel_all: "v2/private/order/cancelAll",
+    replace: "v2/private/order/replace",
+    search: "v2/private/order",
+    list: "v2/private/order/list",
+    closed_pnl: "v2/private/trade/closed-pnl/list",
+};
+
+const INVERSE_FUTURES: Endpoints = Endpoints {
+    order: "futures/private/order/create",
+    position_list: "futures/private/position/list",
+    switch_isolated: "futures/private/position/switch-isolated",
+    leverage: "futures/private/position/leverage/save",
+    trading_stop: "futures/private/position/trading-stop",
+    cancel: "futures/private/order/cancel",
+    cancel_all: "futures/private/order/cancelAll",
+    replace: "futures/private/order/replace",
+    search: "futures/private/order",
+    list: "futures/private/order/list",
+    closed_pnl: "futures/private/trade/closed-pnl/list",
+};
+
+const LEVERAGE_NOT_MODIFIED: i64 = 34036;
+const ISOLATED_NOT_MODIFIED: i64 = 130056;
+
+impl Market {
+    pub fn new(account: &AccountConfig, client: Client) -> Self {
+        Market {
+            client,
+            credentials: account.credentials.clone(),
+            base_url: if account.testnet { BASE_URL_TESTNET } else { MAIN_BASE_URL },
         }
+    }
+
+    async fn call_api(&self, query_params: HashMap<String, Value>, api_path: &str, method: HttpMethod) -> ExchangeResult<Value> {
+        call_api(&self.client, query_params, self.base_url, api_path, method, &self.credentials.api_secret).await?.into_result()
+    }
 
-        response.ret_code == 0
+    async fn symbol_info(&self, symbol: &str) -> ExchangeResult<SymbolInfo> {
+        symbols::symbol_info(&self.client, self.base_url, SymbolList::Legacy, symbol).await
     }
+}
 
-    fn take_profit(symbol: &String, qty: Option<f64>, _side: &OrderSide, take_profit: Option<f64>, _stop_loss: Option<f64>, metadata: &Value) -> bool {
+fn endpoints(symbol: &str) -> &'static Endpoints {
+    match ContractType::from_symbol(symbol) {
+        ContractType::InversePerpetual => &INVERSE_PERPETUAL,
+        ContractType::InverseFutures => &INVERSE_FUTURES,
+        _ => &LINEAR,
+    }
+}
+
+#[async_trait]
+impl MarketApi for Market {
+    fn contract_type(&self, symbol: &str) -> ContractType {
+        ContractType::from_symbol(symbol)
+    }
+
+    fn tracks_orders(&self) -> bool {
+        true
+    }
+
+    fn trails_stops(&self) -> bool {
+        true
+    }
+
+    fn triggers_by(&self, _trigger_by: TriggerBy) -> bool {
+        true
+    }
+
+    async fn order(&self, order: Order) -> ExchangeResult<OrderAck> {
+        let api_path = endpoints(&order.symbol).order;
+        let contract_type = ContractType::from_symbol(&order.symbol);
+        let order = self.symbol_info(&order.symbol).await?.round_order(order)?;
+        let order_request = OrderRequest::new(order);
+        let query_params = order_request.get_query_map(self.credentials.api_key.to_string());
+        let result = self.call_api(query_params, api_path, HttpMethod::Post).await?;
+        Ok(OrderInfo::from_value(&result, contract_type)?.into())
+    }
+
+    async fn take_profit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, _stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
         let take_profit_limit = Order {
             symbol: symbol.to_string(),
             time_in_force: TimeInForce::PostOnly,
@@ -51,91 +157,177 @@ impl MarketApi for Market {
             close_on_trigger: Some(true),
             order_type: OrderType::Limit,
             leverage: Option::None,
-            side: OrderSide::Short,
+            side: side.opposite(),
             take_profit: None,
             stop_loss: None,
+            order_link_id: options.order_link_id.map(String::from),
         };
-        Market::order(take_profit_limit, metadata)
+        self.order(take_profit_limit).await?;
+        Ok(())
     }
 
 
-    fn stop_loss(symbol: &String, _qty: Option<f64>, side: &OrderSide, take_profit: Option<f64>, stop_loss: Option<f64>, metadata: &Value) -> bool {
-        let api_key = metadata["BYBIT_API_KEY"].as_str().unwrap().to_string();
-        let api_secret = metadata["BYBIT_API_SECRET"].as_str().unwrap().to_string();
+    async fn stop_loss(&self, symbol: &str, _qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        let info = self.symbol_info(symbol).await?;
+        let trigger_by = options.trigger_by.unwrap_or(TriggerBy::Last);
+        let trading_stop = TradingStop::new(symbol, side, take_profit.map(|p| info.round_price(p)), stop_loss.map(|p| info.round_price(p)), trigger_by);
+        let query_params = trading_stop.get_query_map(self.credentials.api_key.to_string());
+        self.call_api(query_params, endpoints(symbol).trading_stop, HttpMethod::Post).await?;
+        Ok(())
+    }
+
+    async fn position(&self, symbol: &str) -> ExchangeResult<Option<PositionInformation>> {
+        let pr = PositionRequest::new(symbol);
+        let query_params = pr.get_query_map(self.credentials.api_key.to_string());
+        let result = self.call_api(query_params, endpoints(symbol).position_list, HttpMethod::Get).await?;
+        // Linear lists both sides, inverse perpetual returns a single object and
+        // inverse futures wrap each side in `data`.
+        let positions: Vec<&Value> = match result.as_array() {
+            Some(list) => list.iter().map(|p| p.get("data").unwrap_or(p)).collect(),
+            None if result.is_object() => vec![result.get("data").unwrap_or(&result)],
+            None => Vec::new(),
+        };
+        let position = positions.iter()
+            .find(|p| number_field(p, "size").map(|size| size > Decimal::ZERO).unwrap_or(false))
+            .or_else(|| positions.first());
+        match position {
+            Some(value) => Ok(Some(PositionInformation::from_value(value)?)),
+            None => Ok(None),
+        }
+    }
 
-        let trading_stop = TradingStop::new(symbol, side, take_profit, stop_loss);
-        let query_params = trading_stop.get_query_map(api_key);
-        let response = call_api(query_params, TRADING_STOP_PATH, POST, api_secret);
-        if response.ret_code != 0 {
-            println!("Error: {}:{}", response.ret_code, response.ret_msg);
+    async fn positions(&self) -> ExchangeResult<Vec<PositionInformation>> {
+        let mut positions = Vec::new();
+        for endpoints in [&LINEAR, &INVERSE_PERPETUAL, &INVERSE_FUTURES] {
+            let query_params = PositionRequest::all().get_query_map(self.credentials.api_key.to_string());
+            let result = self.call_api(query_params, endpoints.position_list, HttpMethod::Get).await?;
+            for value in result.as_array().map(|list| list.as_slice()).unwrap_or_default() {
+                let position = PositionInformation::from_value(value.get("data").unwrap_or(value))?;
+                if position.size > Decimal::ZERO {
+                    positions.push(position);
+                }
+            }
         }
-        response.ret_code == 0
+        Ok(positions)
     }
 
-    fn position(symbol: &String, metadata: &Value) -> Option<PositionInformation> {
-        let api_key = metadata["BYBIT_API_KEY"].as_str().unwrap().to_string();
-        let api_secret = metadata["BYBIT_API_SECRET"].as_str().unwrap().to_string();
+    async fn is_in_position(&self, symbol: &str) -> ExchangeResult<bool> {
+        Ok(match self.position(symbol).await? {
+            Some(position_info) => position_info.entry_price > Decimal::ZERO,
+            None => false,
+        })
+    }
 
-        let pr = PositionRequest::new(symbol);
-        let query_params = pr.get_query_map(api_key);
-        let response = call_api(query_params, POSITION_LIST_PATH, HttpMethod::GET, api_secret);
-        let pi = if response.ret_code == 0 {
-            let value = response.result.get(0).unwrap();
-            Option::Some(PositionInformation::from_value(value))
-        } else {
-            Option::None
+    async fn wallet_available_balance(&self, coin: &str) -> ExchangeResult<Decimal> {
+        let wi = WalletInformation::new(coin);
+        let query_params = wi.get_query_map(self.credentials.api_key.to_string());
+        let result = self.call_api(query_params, WALLET_BALANCE_PATH, HttpMethod::Get).await?;
+        let balance = result.get(coin).ok_or_else(|| ExchangeError::decode(format!("available_balance not found for {}", coin)))?;
+        number_field(balance, "available_balance")
+    }
+
+    async fn leverage(&self, symbol: &str, leverage: i32) -> ExchangeResult<()> {
+        let leverage = leverage.min(self.symbol_info(symbol).await?.max_leverage);
+        // Inverse perpetual takes a single leverage for both sides.
+        let query_params = match ContractType::from_symbol(symbol) {
+            ContractType::InversePerpetual => InverseLeverageRequest::new(symbol, leverage).get_query_map(self.credentials.api_key.to_string()),
+            _ => LeverageRequest::new(symbol, leverage, leverage).get_query_map(self.credentials.api_key.to_string()),
         };
-        return pi;
-    }
-
-    fn is_in_position(symbol: &String, metadata: &Value) -> bool {
-        let option_position_info = Market::position(symbol, metadata);
-        return if option_position_info.is_none() {
-            false
-        } else {
-            if option_position_info.unwrap().entry_price > 0.0 {
-                true
-            } else {
-                false
-            }
+        match self.call_api(query_params, endpoints(symbol).leverage, HttpMethod::Post).await {
+            Err(ExchangeError::Exchange { code: LEVERAGE_NOT_MODIFIED, .. }) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    async fn switch_isolated(&self, symbol: &str, isolated: bool, leverage: i32) -> ExchangeResult<()> {
+        let pr = SwitchIsolatedRequest::new(symbol, isolated, leverage, leverage);
+        let query_params = pr.get_query_map(self.credentials.api_key.to_string());
+        match self.call_api(query_params, endpoints(symbol).switch_isolated, HttpMethod::Post).await {
+            Err(ExchangeError::Exchange { code: ISOLATED_NOT_MODIFIED, .. }) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    async fn trailing_stop(&self, symbol: &str, side: &OrderSide, trailing_stop: &TrailingStop) -> ExchangeResult<()> {
+        let info = self.symbol_info(symbol).await?;
+        let trailing_stop = TrailingStop {
+            distance: info.round_price(trailing_stop.distance),
+            active_price: trailing_stop.active_price.map(|p| info.round_price(p)),
         };
+        let query_params = TradingStop::trailing(symbol, side, &trailing_stop).get_query_map(self.credentials.api_key.to_string());
+        self.call_api(query_params, endpoints(symbol).trading_stop, HttpMethod::Post).await?;
+        Ok(())
     }
 
-    fn wallet_available_balance(coin: String, metadata: &Value) -> f64 {
-        let api_key = metadata["BYBIT_API_KEY"].as_str().unwrap().to_string();
-        let api_secret = metadata["BYBIT_API_SECRET"].as_str().unwrap().to_string();
+    async fn qty_step(&self, symbol: &str) -> ExchangeResult<Option<Decimal>> {
+        Ok(Some(self.symbol_info(symbol).await?.qty_step))
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: &str) -> ExchangeResult<()> {
+        let query_params = OrderIdRequest::new(symbol, order_id).get_query_map(self.credentials.api_key.to_string());
+        self.call_api(query_params, endpoints(symbol).cancel, HttpMethod::Post).await?;
+        Ok(())
+    }
 
-        let wi = WalletInformation::new(&coin);
-        let query_params = wi.get_query_map(api_key);
-        let response = call_api(query_params, WALLET_BALANCE_PATH, HttpMethod::GET, api_secret);
-        if response.ret_code != 0 {
-            panic!("Error: {}:{}", response.ret_code, response.ret_msg);
+    async fn cancel_all(&self, symbol: &str) -> ExchangeResult<()> {
+        let query_params = CancelAllRequest::new(symbol).get_query_map(self.credentials.api_key.to_string());
+        self.call_api(query_params, endpoints(symbol).cancel_all, HttpMethod::Post).await?;
+        Ok(())
+    }
+
+    async fn amend_order(&self, symbol: &str, order_id: &str, price: Option<Decimal>, qty: Option<Decimal>) -> ExchangeResult<()> {
+        let info = self.symbol_info(symbol).await?;
+        let qty = match qty {
+            Some(qty) => Some(info.round_qty(symbol, qty)?),
+            None => None,
         };
-        response.result.get(&coin).unwrap().get("available_balance").unwrap().as_f64().unwrap()
+        let query_params = ReplaceOrderRequest::new(symbol, order_id, price.map(|p| info.round_price(p)), qty).get_query_map(self.credentials.api_key.to_string());
+        self.call_api(query_params, endpoints(symbol).replace, HttpMethod::Post).await?;
+        Ok(())
     }
 
-    fn leverage(symbol: &String, leverage: i32, metadata: &Value) -> bool {
-        let api_key = metadata["bybit_api_key"].as_str().unwrap().to_string();
-        let api_secret = metadata["bybit_api_secret"].as_str().unwrap().to_string();
+    async fn order_status(&self, symbol: &str, order_id: &str) -> ExchangeResult<OrderInfo> {
+        let query_params = OrderIdRequest::new(symbol, order_id).get_query_map(self.credentials.api_key.to_string());
+        let result = self.call_api(query_params, endpoints(symbol).search, HttpMethod::Get).await?;
+        OrderInfo::from_value(&result, ContractType::from_symbol(symbol))
+    }
 
-        let pr = LeverageRequest::new(&symbol.to_string(), leverage, leverage);
-        let query_params = pr.get_query_map(api_key);
-        let response = call_api(query_params, LEVERAGE_PATH, HttpMethod::POST, api_secret);
-        response.ret_code == 0 || response.ret_code == 34036
+    async fn open_orders(&self, symbol: &str) -> ExchangeResult<Vec<OrderInfo>> {
+        let query_params = OrderListRequest::open(symbol).get_query_map(self.credentials.api_key.to_string());
+        let result = self.call_api(query_params, endpoints(symbol).list, HttpMethod::Get).await?;
+        let contract_type = ContractType::from_symbol(symbol);
+        result.get("data").and_then(|data| data.as_array()).map(|orders| orders.as_slice()).unwrap_or_default()
+            .iter()
+            .map(|order| OrderInfo::from_value(order, contract_type))
+            .collect()
     }
 
-    fn switch_isolated(symbol: &String, isolated: bool, leverage: i32, metadata: &Value) -> bool {
-        let api_key = metadata["BYBIT_API_KEY"].as_str().unwrap().to_string();
-        let api_secret = metadata["BYBIT_API_SECRET"].as_str().unwrap().to_string();
+    /// The legacy list takes one symbol at a time.
+    async fn closed_pnl(&self, symbol: Option<&str>, start_time: i64) -> ExchangeResult<Vec<ClosedPnl>> {
+        let symbol = symbol.ok_or_else(|| ExchangeError::Unsupported(String::from("closed_pnl of every symbol")))?;
+        let query_params = ClosedPnlRequest::new(symbol, start_time).get_query_map(self.credentials.api_key.to_string());
+        let result = self.call_api(query_params, endpoints(symbol).closed_pnl, HttpMethod::Get).await?;
+        result.get("data").and_then(|data| data.as_array()).map(|list| list.as_slice()).unwrap_or_default()
+            .iter()
+            .map(|value| Ok(ClosedPnl {
+                symbol: symbol.to_string(),
+                pnl: number_field(value, "closed_pnl")?,
+                closed_at: number_field(value, "created_at")?.to_i64().unwrap_or_default() * 1000,
+            }))
+            .collect()
+    }
 
-        let pr = SwitchIsolatedRequest::new(&symbol.to_string(), isolated, leverage, leverage);
-        let query_params = pr.get_query_map(api_key);
-        let response = call_api(query_params, SWITCH_ISOLATED_PATH, HttpMethod::POST, api_secret);
-        response.ret_code == 0 || response.ret_code == 130056
+    async fn find_order(&self, symbol: &str, order_link_id: &str) -> ExchangeResult<Option<OrderInfo>> {
+        let query_params = OrderLinkRequest::new(symbol, order_link_id).get_query_map(self.credentials.api_key.to_string());
+        let result = self.call_api(query_params, endpoints(symbol).search, HttpMethod::Get).await?;
+        if result.get("order_id").is_none() {
+            return Ok(None);
+        }
+        Ok(Some(OrderInfo::from_value(&result, ContractType::from_symbol(symbol))?))
     }
 }
 
-fn call_api(query_params: HashMap<String, Value>, api_path: &str, method: HttpMethod, api_secret: String) -> ApiResponse {
+async fn call_api(client: &Client, query_params: HashMap<String, Value>, base_url: &str, api_path: &str, method: HttpMethod, api_secret: &str) -> ExchangeResult<ApiResponse> {
     let mut map = query_params.clone();
 
     // Create sign key
@@ -153,30 +345,34 @@ fn call_api(query_params: HashMap<String, Value>, api_path: &str, method: HttpMe
     //sort again
     query = sort_query_parameters(&map);
 
-    let base_url = if use_testnet() { BASE_URL_TESTNET } else { MAIN_BASE_URL };
-
     //create url
     let url = match method {
-        HttpMethod::GET => format!("{}/{}?{}", base_url, api_path, query),
-        HttpMethod::DELETE => format!("{}/{}?{}", base_url, api_path, query),
-        HttpMethod::POST => format!("{}/{}?{}", base_url, api_path, query),
-        HttpMethod::PUT => format!("{}/{}?{}", base_url, api_path, query)
+        HttpMethod::Get => format!("{}/{}?{}", base_url, api_path, query),
+        HttpMethod::Delete => format!("{}/{}?{}", base_url, api_path, query),
+        HttpMethod::Post => format!("{}/{}?{}", base_url, api_path, query),
+        HttpMethod::Put => format!("{}/{}?{}", base_url, api_path, query)
     };
 
     // Set Headers
     let mut headers = HeaderMap::new();
-    headers.insert(CONTENT_TYPE, "Content-Type: application/json".parse().unwrap());
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
     // Send request
-    let client = reqwest::blocking::Client::builder().use_rustls_tls().build().unwrap();
-
     let resp = match method {
-        HttpMethod::GET => client.get(url).headers(headers).send(),
-        HttpMethod::POST => client.post(url).send(),
-        HttpMethod::DELETE => client.delete(url).send(),
-        HttpMethod::PUT => client.put(url).send(),
-    };
-    resp.unwrap().json::<ApiResponse>().unwrap()
+        HttpMethod::Get => client.get(url).headers(headers).send(),
+        HttpMethod::Post => client.post(url).send(),
+        HttpMethod::Delete => client.delete(url).send(),
+        HttpMethod::Put => client.put(url).send(),
+    }.await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if status.as_u16() == 403 {
+        return Err(ExchangeError::RateLimited { code: status.as_u16() as i64, message: body });
+    }
+    if !status.is_success() {
+        return Err(ExchangeError::HttpStatus { status: status.as_u16(), body });
+    }
+    Ok(serde_json::from_str::<ApiResponse>(&body)?)
 }
 
 fn sort_query_parameters(query_map: &HashMap<String, Value>) -> String {
@@ -190,14 +386,10 @@ fn sort_query_parameters(query_map: &HashMap<String, Value>) -> String {
     let mut query = String::new();
     for key in key_list {
         let value = &query_map.get(key).unwrap();
-        let p = if value.is_string() {
-            format!("{}={}&", key, value.as_str().unwrap())
-        } else if value.is_boolean() {
-            format!("{}={}&", key, value.as_bool().unwrap())
-        } else if value.is_f64() {
-            format!("{}={}&", key, value.as_f64().unwrap())
-        } else {
-            format!("{}={}&", key, value.as_i64().unwrap())
+        // Parameters are strings, apart from the reduce_only and close_on_trigger flags.
+        let p = match value.as_str() {
+            Some(value) => format!("{}={}&", key, value),
+            None => format!("{}={}&", key, value),
         };
         query.push_str(p.as_str());
     }
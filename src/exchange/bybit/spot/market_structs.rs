@@ -0,0 +1,173 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exchange::bybit::v5::market_structs::get_time_in_force;
+use crate::exchange::error::ExchangeResult;
+use crate::exchange::general::number_field;
+use crate::exchange::structs::{Order, OrderSide, OrderType, PositionInformation};
+
+pub const CATEGORY_SPOT: &str = "spot";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpotOrderRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "side")]
+    pub _side: String,
+    #[serde(rename = "orderType")]
+    pub _order_type: String,
+    #[serde(rename = "qty")]
+    pub _qty: String,
+    #[serde(rename = "price", skip_serializing_if = "Option::is_none")]
+    pub _price: Option<String>,
+    #[serde(rename = "timeInForce")]
+    pub _time_in_force: String,
+    #[serde(rename = "marketUnit", skip_serializing_if = "Option::is_none")]
+    pub _market_unit: Option<String>,
+    #[serde(rename = "isLeverage")]
+    pub _is_leverage: i32,
+    #[serde(rename = "orderFilter", skip_serializing_if = "Option::is_none")]
+    pub _order_filter: Option<String>,
+    #[serde(rename = "triggerPrice", skip_serializing_if = "Option::is_none")]
+    pub _trigger_price: Option<String>,
+    #[serde(rename = "orderLinkId", skip_serializing_if = "Option::is_none")]
+    pub _order_link_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpotBalanceRequest {
+    #[serde(rename = "accountType")]
+    pub _account_type: String,
+    #[serde(rename = "coin", skip_serializing_if = "Option::is_none")]
+    pub _coin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecutionRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "limit")]
+    pub _limit: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpotMarginLeverageRequest {
+    #[serde(rename = "leverage")]
+    pub _leverage: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CrossMarginSwitchRequest {
+    #[serde(rename = "switch")]
+    pub _switch: String,
+}
+
+impl SpotOrderRequest {
+    pub fn new(order: &Order, margin: bool) -> Self {
+        SpotOrderRequest {
+            _category: CATEGORY_SPOT.to_string(),
+            _symbol: order.symbol.to_string(),
+            _side: order.side.to_string(),
+            _order_type: order.order_type.to_string(),
+            _qty: order.qty.to_string(),
+            _price: match order.order_type {
+                OrderType::Limit => Some(order.price.to_string()),
+                OrderType::Market => None,
+            },
+            _time_in_force: get_time_in_force(&order.time_in_force),
+            // Market buys are sized in the quote coin unless told otherwise.
+            _market_unit: match order.order_type {
+                OrderType::Market => Some(String::from("baseCoin")),
+                OrderType::Limit => None,
+            },
+            _is_leverage: if margin { 1 } else { 0 },
+            _order_filter: None,
+            _trigger_price: None,
+            _order_link_id: order.order_link_id.clone(),
+        }
+    }
+
+    /// Conditional market order closing `qty` of a holding opened on `side` once `trigger_price` is crossed.
+    /// Only buying back a short borrows, selling a long is capped at the coins held so that an exit left
+    /// over after its sibling filled cannot open a short.
+    pub fn exit(symbol: &str, side: &OrderSide, qty: Decimal, trigger_price: Decimal, margin: bool, order_link_id: Option<&str>) -> Self {
+        SpotOrderRequest {
+            _category: CATEGORY_SPOT.to_string(),
+            _symbol: symbol.to_string(),
+            _side: side.opposite().to_string(),
+            _order_type: OrderType::Market.to_string(),
+            _qty: qty.to_string(),
+            _price: None,
+            _time_in_force: String::from("GTC"),
+            _market_unit: Some(String::from("baseCoin")),
+            _is_leverage: if margin && *side == OrderSide::Short { 1 } else { 0 },
+            _order_filter: Some(String::from("tpslOrder")),
+            _trigger_price: Some(trigger_price.to_string()),
+            _order_link_id: order_link_id.map(String::from),
+        }
+    }
+}
+
+impl SpotBalanceRequest {
+    pub fn new(account_type: &str, coin: &str) -> Self {
+        SpotBalanceRequest {
+            _account_type: account_type.to_string(),
+            _coin: Some(coin.to_string()),
+        }
+    }
+
+    /// Balances of every coin.
+    pub fn all(account_type: &str) -> Self {
+        SpotBalanceRequest {
+            _account_type: account_type.to_string(),
+            _coin: None,
+        }
+    }
+}
+
+impl ExecutionRequest {
+    pub fn last(symbol: &str) -> Self {
+        ExecutionRequest {
+            _category: CATEGORY_SPOT.to_string(),
+            _symbol: symbol.to_string(),
+            _limit: 1,
+        }
+    }
+}
+
+impl SpotMarginLeverageRequest {
+    pub fn new(leverage: i32) -> Self {
+        SpotMarginLeverageRequest { _leverage: leverage.to_string() }
+    }
+}
+
+impl CrossMarginSwitchRequest {
+    pub fn on() -> Self {
+        CrossMarginSwitchRequest { _switch: String::from("1") }
+    }
+}
+
+impl PositionInformation {
+    /// Spot has no positions, a holding is the coin balance net of borrowing.
+    pub fn from_spot_balance(symbol: &str, value: &Value, entry_price: Decimal) -> ExchangeResult<PositionInformation> {
+        let holding = number_field(value, "walletBalance")? - number_field(value, "borrowAmount").unwrap_or_default();
+        let size = holding.abs();
+
+        Ok(PositionInformation {
+            entry_price,
+            free_qty: number_field(value, "availableToWithdraw").or_else(|_| number_field(value, "free")).unwrap_or(size).min(size),
+            is_isolated: false,
+            leverage: 1,
+            liq_price: Decimal::ZERO,
+            side: if holding < Decimal::ZERO { OrderSide::Short } else { OrderSide::Long },
+            size,
+            symbol: symbol.to_string(),
+            unrealised_pnl: Decimal::ZERO,
+        })
+    }
+}
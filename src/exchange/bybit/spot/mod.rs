@@ -0,0 +1,274 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::exchange::account::{AccountConfig, AccountType, Credentials};
+use crate::exchange::bybit::spot::market_structs::*;
+use crate::exchange::bybit::symbols::{self, SymbolInfo, SymbolList};
+use crate::exchange::bybit::v5::{AMEND_ORDER_PATH, CANCEL_ALL_PATH, CANCEL_ORDER_PATH, call_api, OPEN_ORDERS_PATH, orders_from_list};
+use crate::exchange::bybit::v5::market_structs::{AmendOrderRequest, CancelAllRequest, CancelOrderRequest, OrderQueryRequest};
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{MarketApi, number_field};
+use crate::exchange::structs::*;
+
+mod market_structs;
+
+/// Bybit spot and spot margin client on the V5 API.
+///
+/// Spot has no positions or take profit / stop loss on a position, the holding of the base
+/// coin is reported as the position and exits are placed as conditional market orders.
+pub struct Market {
+    client: Client,
+    credentials: Credentials,
+    base_url: &'static str,
+    unified: bool,
+    margin: bool,
+}
+
+const MAIN_BASE_URL: &str = "https://api.bybit.com";
+const BASE_URL_TESTNET: &str = "https://api-testnet.bybit.com";
+
+const ORDER_PATH: &str = "/v5/order/create";
+const WALLET_BALANCE_PATH: &str = "/v5/account/wallet-balance";
+const EXECUTION_LIST_PATH: &str = "/v5/execution/list";
+const SPOT_MARGIN_LEVERAGE_PATH: &str = "/v5/spot-margin-trade/set-leverage";
+const CROSS_MARGIN_SWITCH_PATH: &str = "/v5/spot-cross-margin-trade/switch";
+
+/// Spot keeps regular and conditional (take profit / stop loss) orders apart.
+const ORDER_FILTERS: [&str; 2] = ["Order", "tpslOrder"];
+
+/// Holdings worth less than this (in the quote coin) are left over dust, not a position.
+const DUST_VALUE: Decimal = Decimal::TEN;
+
+/// Coins holdings are valued in, they are the balance rather than a position.
+const QUOTE_COINS: [&str; 2] = ["USDT", "USDC"];
+
+impl Market {
+    pub fn new(account: &AccountConfig, client: Client, margin: bool) -> Self {
+        Market {
+            client,
+            credentials: account.credentials.clone(),
+            base_url: if account.testnet { BASE_URL_TESTNET } else { MAIN_BASE_URL },
+            unified: account.account_type == AccountType::Unified,
+            margin,
+        }
+    }
+
+    async fn get<T: Serialize>(&self, api_path: &str, request: &T) -> ExchangeResult<Value> {
+        let query = serde_urlencoded::to_string(request).map_err(|e| ExchangeError::decode(e.to_string()))?;
+        call_api(&self.client, self.base_url, api_path, HttpMethod::Get, query, &self.credentials).await?.into_result()
+    }
+
+    async fn post<T: Serialize>(&self, api_path: &str, request: &T) -> ExchangeResult<Value> {
+        let body = serde_json::to_string(request)?;
+        call_api(&self.client, self.base_url, api_path, HttpMethod::Post, body, &self.credentials).await?.into_result()
+    }
+
+    async fn symbol_info(&self, symbol: &str) -> ExchangeResult<SymbolInfo> {
+        symbols::symbol_info(&self.client, self.base_url, SymbolList::V5(CATEGORY_SPOT), symbol).await
+    }
+
+    fn account_type(&self) -> &'static str {
+        if self.unified { "UNIFIED" } else { "SPOT" }
+    }
+
+    async fn coin_balances(&self, request: &SpotBalanceRequest) -> ExchangeResult<Vec<Value>> {
+        let result = self.get(WALLET_BALANCE_PATH, request).await?;
+        Ok(result.get("list").and_then(|list| list.get(0))
+            .and_then(|account| account.get("coin"))
+            .and_then(|coins| coins.as_array())
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn coin_balance(&self, coin: &str) -> ExchangeResult<Option<Value>> {
+        let balances = self.coin_balances(&SpotBalanceRequest::new(self.account_type(), coin)).await?;
+        Ok(balances.into_iter().find(|c| c.get("coin").and_then(|n| n.as_str()) == Some(coin)))
+    }
+
+    /// Price of the last fill on `symbol`, spot keeps no average entry price.
+    async fn last_fill_price(&self, symbol: &str) -> ExchangeResult<Decimal> {
+        let result = self.get(EXECUTION_LIST_PATH, &ExecutionRequest::last(symbol)).await?;
+        match result.get("list").and_then(|list| list.get(0)) {
+            Some(execution) => number_field(execution, "execPrice"),
+            None => Ok(Decimal::ZERO),
+        }
+    }
+
+    async fn exit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, trigger_price: Decimal, order_link_id: Option<&str>) -> ExchangeResult<()> {
+        let qty = match qty {
+            Some(qty) => qty,
+            None => self.position(symbol).await?.map(|p| p.size).unwrap_or_default(),
+        };
+        let info = self.symbol_info(symbol).await?;
+        let request = SpotOrderRequest::exit(symbol, side, info.round_qty(symbol, qty)?, info.round_price(trigger_price), self.margin, order_link_id);
+        self.post(ORDER_PATH, &request).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MarketApi for Market {
+    fn contract_type(&self, _symbol: &str) -> ContractType {
+        if self.margin { ContractType::SpotMargin } else { ContractType::Spot }
+    }
+
+    fn tracks_orders(&self) -> bool {
+        true
+    }
+
+    async fn order(&self, order: Order) -> ExchangeResult<OrderAck> {
+        let order = self.symbol_info(&order.symbol).await?.round_order(order)?;
+        let request = SpotOrderRequest::new(&order, self.margin);
+        let result = self.post(ORDER_PATH, &request).await?;
+        OrderAck::from_v5(&result)
+    }
+
+    async fn take_profit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, _stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        match take_profit {
+            Some(price) => self.exit(symbol, qty, side, price, options.order_link_id).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn stop_loss(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        // Both exits are sized for the whole holding. Once one fills the other is rejected when it triggers,
+        // a long's exits never borrow so the leftover cannot sell coins that are no longer held.
+        if let Some(price) = stop_loss {
+            self.exit(symbol, qty, side, price, options.order_link_id).await?;
+        }
+        if let Some(price) = take_profit {
+            self.exit(symbol, qty, side, price, None).await?;
+        }
+        Ok(())
+    }
+
+    async fn position(&self, symbol: &str) -> ExchangeResult<Option<PositionInformation>> {
+        let base_coin = spot_base_coin(symbol);
+        let balance = match self.coin_balance(&base_coin).await? {
+            Some(balance) => balance,
+            None => return Ok(None),
+        };
+        let entry_price = self.last_fill_price(symbol).await?;
+        let position = PositionInformation::from_spot_balance(symbol, &balance, entry_price)?;
+        Ok(if position.size > Decimal::ZERO { Some(position) } else { None })
+    }
+
+    /// Holdings of every coin but the quote coins, as `<coin>USDT` at the price of their last fill.
+    async fn positions(&self) -> ExchangeResult<Vec<PositionInformation>> {
+        let mut positions = Vec::new();
+        for balance in self.coin_balances(&SpotBalanceRequest::all(self.account_type())).await? {
+            let coin = balance.get("coin").and_then(|c| c.as_str()).unwrap_or_default();
+            if coin.is_empty() || QUOTE_COINS.contains(&coin) {
+                continue;
+            }
+            let symbol = format!("{}USDT", coin);
+            let entry_price = match self.last_fill_price(&symbol).await {
+                Ok(price) => price,
+                // Coins without a USDT market cannot be valued.
+                Err(ExchangeError::Exchange { .. }) => continue,
+                Err(e) => return Err(e),
+            };
+            let position = PositionInformation::from_spot_balance(&symbol, &balance, entry_price)?;
+            if position.size * position.entry_price >= DUST_VALUE {
+                positions.push(position);
+            }
+        }
+        Ok(positions)
+    }
+
+    async fn is_in_position(&self, symbol: &str) -> ExchangeResult<bool> {
+        Ok(match self.position(symbol).await? {
+            Some(position_info) => position_info.size * position_info.entry_price >= DUST_VALUE,
+            None => false,
+        })
+    }
+
+    async fn wallet_available_balance(&self, coin: &str) -> ExchangeResult<Decimal> {
+        match self.coin_balance(coin).await? {
+            Some(balance) => number_field(&balance, "availableToWithdraw")
+                .or_else(|_| number_field(&balance, "free"))
+                .or_else(|_| number_field(&balance, "walletBalance")),
+            None => Ok(Decimal::ZERO),
+        }
+    }
+
+    async fn leverage(&self, _symbol: &str, leverage: i32) -> ExchangeResult<()> {
+        if !self.margin {
+            return Ok(());
+        }
+        // Unified accounts pick the spot margin leverage, classic accounts only switch cross margin on.
+        if self.unified {
+            self.post(SPOT_MARGIN_LEVERAGE_PATH, &SpotMarginLeverageRequest::new(leverage)).await?;
+        } else {
+            self.post(CROSS_MARGIN_SWITCH_PATH, &CrossMarginSwitchRequest::on()).await?;
+        }
+        Ok(())
+    }
+
+    async fn switch_isolated(&self, _symbol: &str, _isolated: bool, _leverage: i32) -> ExchangeResult<()> {
+        // Spot margin is always cross.
+        Ok(())
+    }
+
+    async fn qty_step(&self, symbol: &str) -> ExchangeResult<Option<Decimal>> {
+        Ok(Some(self.symbol_info(symbol).await?.qty_step))
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: &str) -> ExchangeResult<()> {
+        self.post(CANCEL_ORDER_PATH, &CancelOrderRequest::new(CATEGORY_SPOT, symbol, order_id)).await?;
+        Ok(())
+    }
+
+    async fn cancel_all(&self, symbol: &str) -> ExchangeResult<()> {
+        for order_filter in ORDER_FILTERS.iter() {
+            self.post(CANCEL_ALL_PATH, &CancelAllRequest::new(CATEGORY_SPOT, symbol, Some(order_filter))).await?;
+        }
+        Ok(())
+    }
+
+    async fn amend_order(&self, symbol: &str, order_id: &str, price: Option<Decimal>, qty: Option<Decimal>) -> ExchangeResult<()> {
+        let info = self.symbol_info(symbol).await?;
+        let qty = match qty {
+            Some(qty) => Some(info.round_qty(symbol, qty)?),
+            None => None,
+        };
+        self.post(AMEND_ORDER_PATH, &AmendOrderRequest::new(CATEGORY_SPOT, symbol, order_id, price.map(|p| info.round_price(p)), qty)).await?;
+        Ok(())
+    }
+
+    async fn order_status(&self, symbol: &str, order_id: &str) -> ExchangeResult<OrderInfo> {
+        let result = self.get(OPEN_ORDERS_PATH, &OrderQueryRequest::by_id(CATEGORY_SPOT, symbol, order_id)).await?;
+        orders_from_list(&result)?.into_iter().next()
+            .ok_or_else(|| ExchangeError::Exchange { code: -1, message: format!("Order {} not found", order_id) })
+    }
+
+    async fn open_orders(&self, symbol: &str) -> ExchangeResult<Vec<OrderInfo>> {
+        let mut orders = Vec::new();
+        for order_filter in ORDER_FILTERS.iter() {
+            let result = self.get(OPEN_ORDERS_PATH, &OrderQueryRequest::open(CATEGORY_SPOT, symbol, Some(order_filter))).await?;
+            orders.extend(orders_from_list(&result)?);
+        }
+        Ok(orders)
+    }
+
+    async fn find_order(&self, symbol: &str, order_link_id: &str) -> ExchangeResult<Option<OrderInfo>> {
+        let result = self.get(OPEN_ORDERS_PATH, &OrderQueryRequest::by_link_id(CATEGORY_SPOT, symbol, order_link_id)).await?;
+        Ok(orders_from_list(&result)?.into_iter().next())
+    }
+}
+
+/// Base coin of a spot symbol such as `BTCUSDT`.
+fn spot_base_coin(symbol: &str) -> String {
+    let symbol = symbol.to_uppercase();
+    for quote in ["USDT", "USDC", "BTC", "ETH"].iter() {
+        if let Some(base) = symbol.strip_suffix(quote) {
+            if !base.is_empty() {
+                return base.to_string();
+            }
+        }
+    }
+    symbol
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use log::info;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde_json::Value;
+
+use crate::common::environments::symbol_info_refresh_secs;
+use crate::exchange::bybit::market_structs::ApiResponse;
+use crate::exchange::bybit::v5::market_structs::ApiResponse as V5ApiResponse;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{number_field, round_to_step};
+use crate::exchange::structs::{Order, OrderType};
+
+const SYMBOLS_PATH: &str = "v2/public/symbols";
+const INSTRUMENTS_PATH: &str = "v5/market/instruments-info";
+
+/// A symbol missing from a list loaded this recently is unknown, without loading the list again.
+const UNKNOWN_SYMBOL_RELOAD: Duration = Duration::from_secs(60);
+
+/// Where a symbol list is loaded from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolList {
+    /// Derivatives of the legacy API.
+    Legacy,
+    /// Instruments of a V5 category, `linear`, `inverse` or `spot`.
+    V5(&'static str),
+}
+
+/// Trading rules of one symbol.
+#[derive(Clone, Debug)]
+pub struct SymbolInfo {
+    pub tick_size: Decimal,
+    pub qty_step: Decimal,
+    pub min_qty: Decimal,
+    pub max_qty: Decimal,
+    pub max_leverage: i32,
+}
+
+/// Symbols of one list loaded from one base URL, testnet and mainnet list different symbols.
+struct Symbols {
+    loaded_at: Instant,
+    symbols: HashMap<String, SymbolInfo>,
+}
+
+static CACHE: OnceLock<RwLock<HashMap<(&'static str, SymbolList), Symbols>>> = OnceLock::new();
+
+fn cache() -> &'static RwLock<HashMap<(&'static str, SymbolList), Symbols>> {
+    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Trading rules of `symbol` in `list`, the list is reloaded once it is older than the refresh
+/// interval or does not know the symbol yet. A symbol still missing is reported unknown for a
+/// minute before the list is loaded again.
+pub async fn symbol_info(client: &Client, base_url: &'static str, list: SymbolList, symbol: &str) -> ExchangeResult<SymbolInfo> {
+    let unknown = || ExchangeError::InvalidOrder(format!("Unknown symbol {}", symbol));
+    let refresh = Duration::from_secs(symbol_info_refresh_secs());
+    if let Some(symbols) = cache().read().unwrap().get(&(base_url, list)) {
+        if symbols.loaded_at.elapsed() < refresh {
+            match symbols.symbols.get(symbol) {
+                Some(info) => return Ok(info.clone()),
+                None if symbols.loaded_at.elapsed() < UNKNOWN_SYMBOL_RELOAD => return Err(unknown()),
+                None => {}
+            }
+        }
+    }
+
+    let symbols = match list {
+        SymbolList::Legacy => load(client, base_url).await?,
+        SymbolList::V5(category) => load_v5(client, base_url, category).await?,
+    };
+    info!("Loaded {} symbols from {} {:?}", symbols.len(), base_url, list);
+    let info = symbols.get(symbol).cloned();
+    cache().write().unwrap().insert((base_url, list), Symbols { loaded_at: Instant::now(), symbols });
+    info.ok_or_else(unknown)
+}
+
+async fn load(client: &Client, base_url: &str) -> ExchangeResult<HashMap<String, SymbolInfo>> {
+    let body = get(client, format!("{}/{}", base_url, SYMBOLS_PATH)).await?;
+    let result = serde_json::from_str::<ApiResponse>(&body)?.into_result()?;
+    result.as_array().map(|list| list.as_slice()).unwrap_or_default()
+        .iter()
+        .map(|symbol| Ok((id(symbol, "name")?, SymbolInfo::from_value(symbol)?)))
+        .collect()
+}
+
+/// Instruments of a V5 `category`, linear and inverse are listed a page at a time.
+async fn load_v5(client: &Client, base_url: &str, category: &str) -> ExchangeResult<HashMap<String, SymbolInfo>> {
+    let mut symbols = HashMap::new();
+    let mut cursor = String::new();
+    loop {
+        let url = format!("{}/{}?category={}&limit=1000&cursor={}", base_url, INSTRUMENTS_PATH, category, cursor);
+        let result = serde_json::from_str::<V5ApiResponse>(&get(client, url).await?)?.into_result()?;
+        for symbol in result.get("list").and_then(|list| list.as_array()).map(|list| list.as_slice()).unwrap_or_default() {
+            symbols.insert(id(symbol, "symbol")?, SymbolInfo::from_v5(symbol)?);
+        }
+        cursor = match result.get("nextPageCursor").and_then(|cursor| cursor.as_str()) {
+            Some(cursor) if !cursor.is_empty() => cursor.to_string(),
+            _ => return Ok(symbols),
+        };
+    }
+}
+
+async fn get(client: &Client, url: String) -> ExchangeResult<String> {
+    let resp = client.get(url).send().await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if !status.is_success() {
+        return Err(ExchangeError::HttpStatus { status: status.as_u16(), body });
+    }
+    Ok(body)
+}
+
+fn id(symbol: &Value, field: &str) -> ExchangeResult<String> {
+    symbol.get(field).and_then(|name| name.as_str())
+        .map(String::from)
+        .ok_or_else(|| ExchangeError::decode(format!("{} not found", field)))
+}
+
+impl SymbolInfo {
+    fn from_value(value: &Value) -> ExchangeResult<SymbolInfo> {
+        let price_filter = value.get("price_filter").ok_or_else(|| ExchangeError::decode("price_filter not found"))?;
+        let lot_size_filter = value.get("lot_size_filter").ok_or_else(|| ExchangeError::decode("lot_size_filter not found"))?;
+        let leverage_filter = value.get("leverage_filter").ok_or_else(|| ExchangeError::decode("leverage_filter not found"))?;
+        Ok(SymbolInfo {
+            tick_size: number_field(price_filter, "tick_size")?,
+            qty_step: number_field(lot_size_filter, "qty_step")?,
+            min_qty: number_field(lot_size_filter, "min_trading_qty")?,
+            max_qty: number_field(lot_size_filter, "max_trading_qty")?,
+            max_leverage: number_field(leverage_filter, "max_leverage")?.to_i32().unwrap_or_default(),
+        })
+    }
+
+    fn from_v5(value: &Value) -> ExchangeResult<SymbolInfo> {
+        let price_filter = value.get("priceFilter").ok_or_else(|| ExchangeError::decode("priceFilter not found"))?;
+        let lot_size_filter = value.get("lotSizeFilter").ok_or_else(|| ExchangeError::decode("lotSizeFilter not found"))?;
+        // Spot has no leverage filter and steps its quantity by the base coin precision.
+        let max_leverage = value.get("leverageFilter").and_then(|filter| number_field(filter, "maxLeverage").ok());
+        Ok(SymbolInfo {
+            tick_size: number_field(price_filter, "tickSize")?,
+            qty_step: number_field(lot_size_filter, "qtyStep").or_else(|_| number_field(lot_size_filter, "basePrecision"))?,
+            min_qty: number_field(lot_size_filter, "minOrderQty")?,
+            max_qty: number_field(lot_size_filter, "maxOrderQty")?,
+            max_leverage: max_leverage.and_then(|leverage| leverage.to_i32()).unwrap_or_default(),
+        })
+    }
+
+    /// Nearest valid price.
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        round_to_step(price, self.tick_size, Decimal::round)
+    }
+
+    /// Quantity rounded down to the lot step and capped at the maximum, below the minimum is an error.
+    pub fn round_qty(&self, symbol: &str, qty: Decimal) -> ExchangeResult<Decimal> {
+        let rounded = round_to_step(qty.min(self.max_qty), self.qty_step, Decimal::floor);
+        if rounded < self.min_qty {
+            return Err(ExchangeError::InvalidOrder(format!("Order qty {} for {} is below the minimum {}", qty, symbol, self.min_qty)));
+        }
+        Ok(rounded)
+    }
+
+    /// `order` with its price, quantity and exit prices on the symbol's grid.
+    pub fn round_order(&self, mut order: Order) -> ExchangeResult<Order> {
+        order.qty = self.round_qty(&order.symbol, order.qty)?;
+        if order.order_type == OrderType::Limit {
+            order.price = self.round_price(order.price);
+        }
+        order.take_profit = order.take_profit.map(|price| self.round_price(price));
+        order.stop_loss = order.stop_loss.map(|price| self.round_price(price));
+        Ok(order)
+    }
+}
@@ -0,0 +1,443 @@
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::number_field;
+use crate::exchange::bybit::market_structs::parse_order_status;
+use crate::exchange::general::id_field;
+use crate::exchange::structs::{Order, OrderAck, OrderInfo, OrderSide, OrderType, PositionInformation, TimeInForce, TrailingStop, TriggerBy};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "side")]
+    pub _side: String,
+    #[serde(rename = "orderType")]
+    pub _order_type: String,
+    #[serde(rename = "qty")]
+    pub _qty: String,
+    #[serde(rename = "price", skip_serializing_if = "Option::is_none")]
+    pub _price: Option<String>,
+    #[serde(rename = "timeInForce")]
+    pub _time_in_force: String,
+    #[serde(rename = "takeProfit", skip_serializing_if = "Option::is_none")]
+    pub _take_profit: Option<String>,
+    #[serde(rename = "stopLoss", skip_serializing_if = "Option::is_none")]
+    pub _stop_loss: Option<String>,
+    #[serde(rename = "reduceOnly", skip_serializing_if = "Option::is_none")]
+    pub _reduce_only: Option<bool>,
+    #[serde(rename = "closeOnTrigger", skip_serializing_if = "Option::is_none")]
+    pub _close_on_trigger: Option<bool>,
+    #[serde(rename = "orderLinkId", skip_serializing_if = "Option::is_none")]
+    pub _order_link_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TradingStop {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "takeProfit", skip_serializing_if = "Option::is_none")]
+    pub _take_profit: Option<String>,
+    #[serde(rename = "stopLoss", skip_serializing_if = "Option::is_none")]
+    pub _stop_loss: Option<String>,
+    #[serde(rename = "tpTriggerBy", skip_serializing_if = "Option::is_none")]
+    pub _tp_trigger_by: Option<String>,
+    #[serde(rename = "slTriggerBy", skip_serializing_if = "Option::is_none")]
+    pub _sl_trigger_by: Option<String>,
+    #[serde(rename = "trailingStop", skip_serializing_if = "Option::is_none")]
+    pub _trailing_stop: Option<String>,
+    #[serde(rename = "activePrice", skip_serializing_if = "Option::is_none")]
+    pub _active_price: Option<String>,
+    #[serde(rename = "tpslMode")]
+    pub _tpsl_mode: String,
+    #[serde(rename = "positionIdx")]
+    pub _position_idx: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LeverageRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "buyLeverage")]
+    pub _buy_leverage: String,
+    #[serde(rename = "sellLeverage")]
+    pub _sell_leverage: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarginModeRequest {
+    #[serde(rename = "setMarginMode")]
+    pub _set_margin_mode: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PositionRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol", skip_serializing_if = "Option::is_none")]
+    pub _symbol: Option<String>,
+    #[serde(rename = "settleCoin", skip_serializing_if = "Option::is_none")]
+    pub _settle_coin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WalletBalanceRequest {
+    #[serde(rename = "accountType")]
+    pub _account_type: String,
+    #[serde(rename = "coin")]
+    pub _coin: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelOrderRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "orderId")]
+    pub _order_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelAllRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "orderFilter", skip_serializing_if = "Option::is_none")]
+    pub _order_filter: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AmendOrderRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "orderId")]
+    pub _order_id: String,
+    #[serde(rename = "price", skip_serializing_if = "Option::is_none")]
+    pub _price: Option<String>,
+    #[serde(rename = "qty", skip_serializing_if = "Option::is_none")]
+    pub _qty: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClosedPnlRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol", skip_serializing_if = "Option::is_none")]
+    pub _symbol: Option<String>,
+    #[serde(rename = "startTime")]
+    pub _start_time: i64,
+    #[serde(rename = "limit")]
+    pub _limit: i32,
+    #[serde(rename = "cursor", skip_serializing_if = "Option::is_none")]
+    pub _cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderQueryRequest {
+    #[serde(rename = "category")]
+    pub _category: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "orderId", skip_serializing_if = "Option::is_none")]
+    pub _order_id: Option<String>,
+    #[serde(rename = "orderLinkId", skip_serializing_if = "Option::is_none")]
+    pub _order_link_id: Option<String>,
+    #[serde(rename = "orderFilter", skip_serializing_if = "Option::is_none")]
+    pub _order_filter: Option<String>,
+    #[serde(rename = "openOnly")]
+    pub _open_only: i32,
+    #[serde(rename = "limit")]
+    pub _limit: i32,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ApiResponse {
+    #[serde(rename = "retCode")]
+    pub ret_code: i64,
+    #[serde(rename = "retMsg", default)]
+    pub ret_msg: String,
+    #[serde(default)]
+    pub result: Value,
+}
+
+impl ApiResponse {
+    pub fn into_result(self) -> ExchangeResult<Value> {
+        let code = self.ret_code;
+        let message = self.ret_msg;
+        match code {
+            0 => Ok(self.result),
+            10003 | 10004 | 10005 | 10007 | 33004 => Err(ExchangeError::Auth { code, message }),
+            10006 | 10018 => Err(ExchangeError::RateLimited { code, message }),
+            110004 | 110007 | 110012 | 110045 | 170131 => Err(ExchangeError::InsufficientBalance { code, message }),
+            _ => Err(ExchangeError::Exchange { code, message }),
+        }
+    }
+}
+
+impl OrderRequest {
+    pub fn new(category: &str, order: &Order) -> Self {
+        OrderRequest {
+            _category: category.to_string(),
+            _symbol: order.symbol.to_string(),
+            _side: order.side.to_string(),
+            _order_type: order.order_type.to_string(),
+            _qty: order.qty.to_string(),
+            _price: match order.order_type {
+                OrderType::Limit => Some(order.price.to_string()),
+                OrderType::Market => None,
+            },
+            _time_in_force: get_time_in_force(&order.time_in_force),
+            _take_profit: order.take_profit.map(|p| p.to_string()),
+            _stop_loss: order.stop_loss.map(|p| p.to_string()),
+            _reduce_only: order.reduce_only,
+            _close_on_trigger: order.close_on_trigger,
+            _order_link_id: order.order_link_id.clone(),
+        }
+    }
+}
+
+impl TradingStop {
+    pub fn new(category: &str, symbol: &str, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, trigger_by: TriggerBy) -> Self {
+        TradingStop {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _take_profit: take_profit.map(|p| p.to_string()),
+            _stop_loss: stop_loss.map(|p| p.to_string()),
+            _tp_trigger_by: take_profit.map(|_| trigger_by.to_string()),
+            _sl_trigger_by: stop_loss.map(|_| trigger_by.to_string()),
+            _trailing_stop: None,
+            _active_price: None,
+            _tpsl_mode: String::from("Full"),
+            _position_idx: 0,
+        }
+    }
+
+    pub fn trailing(category: &str, symbol: &str, trailing_stop: &TrailingStop) -> Self {
+        TradingStop {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _take_profit: None,
+            _stop_loss: None,
+            _tp_trigger_by: None,
+            _sl_trigger_by: None,
+            _trailing_stop: Some(trailing_stop.distance.to_string()),
+            _active_price: trailing_stop.active_price.map(|p| p.to_string()),
+            _tpsl_mode: String::from("Full"),
+            _position_idx: 0,
+        }
+    }
+}
+
+impl LeverageRequest {
+    pub fn new(category: &str, symbol: &str, leverage: i32) -> Self {
+        LeverageRequest {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _buy_leverage: leverage.to_string(),
+            _sell_leverage: leverage.to_string(),
+        }
+    }
+}
+
+impl MarginModeRequest {
+    pub fn new(isolated: bool) -> Self {
+        MarginModeRequest {
+            _set_margin_mode: String::from(if isolated { "ISOLATED_MARGIN" } else { "REGULAR_MARGIN" }),
+        }
+    }
+}
+
+impl PositionRequest {
+    pub fn new(category: &str, symbol: &str) -> Self {
+        PositionRequest {
+            _category: category.to_string(),
+            _symbol: Some(symbol.to_string()),
+            _settle_coin: None,
+        }
+    }
+
+    /// Positions on every symbol settled in `settle_coin`.
+    pub fn settled_in(category: &str, settle_coin: &str) -> Self {
+        PositionRequest {
+            _category: category.to_string(),
+            _symbol: None,
+            _settle_coin: Some(settle_coin.to_string()),
+        }
+    }
+
+    /// Positions on every symbol of `category`, only inverse lists them without a settle coin.
+    pub fn all(category: &str) -> Self {
+        PositionRequest {
+            _category: category.to_string(),
+            _symbol: None,
+            _settle_coin: None,
+        }
+    }
+}
+
+impl WalletBalanceRequest {
+    pub fn new(coin: &str) -> Self {
+        WalletBalanceRequest {
+            _account_type: String::from("UNIFIED"),
+            _coin: coin.to_string(),
+        }
+    }
+}
+
+impl CancelOrderRequest {
+    pub fn new(category: &str, symbol: &str, order_id: &str) -> Self {
+        CancelOrderRequest {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _order_id: order_id.to_string(),
+        }
+    }
+}
+
+impl CancelAllRequest {
+    pub fn new(category: &str, symbol: &str, order_filter: Option<&str>) -> Self {
+        CancelAllRequest {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _order_filter: order_filter.map(|f| f.to_string()),
+        }
+    }
+}
+
+impl AmendOrderRequest {
+    pub fn new(category: &str, symbol: &str, order_id: &str, price: Option<Decimal>, qty: Option<Decimal>) -> Self {
+        AmendOrderRequest {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _order_id: order_id.to_string(),
+            _price: price.map(|p| p.to_string()),
+            _qty: qty.map(|q| q.to_string()),
+        }
+    }
+}
+
+impl ClosedPnlRequest {
+    pub fn new(category: &str, symbol: Option<&str>, start_time: i64) -> Self {
+        ClosedPnlRequest {
+            _category: category.to_string(),
+            _symbol: symbol.map(String::from),
+            _start_time: start_time,
+            _limit: 100,
+            _cursor: None,
+        }
+    }
+}
+
+impl OrderQueryRequest {
+    pub fn by_id(category: &str, symbol: &str, order_id: &str) -> Self {
+        OrderQueryRequest {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _order_id: Some(order_id.to_string()),
+            _order_link_id: None,
+            _order_filter: None,
+            _open_only: 0,
+            _limit: 1,
+        }
+    }
+
+    pub fn by_link_id(category: &str, symbol: &str, order_link_id: &str) -> Self {
+        OrderQueryRequest {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _order_id: None,
+            _order_link_id: Some(order_link_id.to_string()),
+            _order_filter: None,
+            _open_only: 0,
+            _limit: 1,
+        }
+    }
+
+    /// Open orders on `symbol`, `order_filter` selects conditional orders on spot.
+    pub fn open(category: &str, symbol: &str, order_filter: Option<&str>) -> Self {
+        OrderQueryRequest {
+            _category: category.to_string(),
+            _symbol: symbol.to_string(),
+            _order_id: None,
+            _order_link_id: None,
+            _order_filter: order_filter.map(|f| f.to_string()),
+            _open_only: 0,
+            _limit: 50,
+        }
+    }
+}
+
+impl OrderAck {
+    /// V5 create only acknowledges the order, fills are read with `order_status`.
+    pub fn from_v5(value: &Value) -> ExchangeResult<OrderAck> {
+        let order_link_id = value.get("orderLinkId").and_then(|id| id.as_str()).unwrap_or_default();
+        Ok(OrderAck::accepted(&id_field(value, "orderId")?, order_link_id))
+    }
+}
+
+impl OrderInfo {
+    pub fn from_v5(value: &Value) -> ExchangeResult<OrderInfo> {
+        let side = if value.get("side").and_then(|s| s.as_str()) == Some("Sell") { OrderSide::Short } else { OrderSide::Long };
+        let order_type = if value.get("orderType").and_then(|t| t.as_str()) == Some("Limit") { OrderType::Limit } else { OrderType::Market };
+        let status = value.get("orderStatus").and_then(|s| s.as_str())
+            .ok_or_else(|| ExchangeError::decode("orderStatus not found"))?;
+        // Unset prices come back as "" or "0".
+        let trigger_price = number_field(value, "triggerPrice").unwrap_or_default();
+
+        Ok(OrderInfo {
+            order_id: value.get("orderId").and_then(|id| id.as_str()).unwrap_or_default().to_string(),
+            order_link_id: value.get("orderLinkId").and_then(|id| id.as_str()).unwrap_or_default().to_string(),
+            symbol: value.get("symbol").and_then(|s| s.as_str()).unwrap_or_default().to_string(),
+            side,
+            order_type,
+            status: parse_order_status(status)?,
+            price: number_field(value, "price").unwrap_or_default(),
+            qty: number_field(value, "qty")?,
+            executed_qty: number_field(value, "cumExecQty").unwrap_or_default(),
+            avg_price: number_field(value, "avgPrice").unwrap_or_default(),
+            trigger_price: if trigger_price > Decimal::ZERO { Some(trigger_price) } else { None },
+            reduce_only: value.get("reduceOnly").and_then(|r| r.as_bool()).unwrap_or(false),
+        })
+    }
+}
+
+impl PositionInformation {
+    pub fn from_v5(value: &Value) -> ExchangeResult<PositionInformation> {
+        let side = if value.get("side").and_then(|s| s.as_str()) == Some("Sell") { OrderSide::Short } else { OrderSide::Long };
+        let size = number_field(value, "size")?;
+
+        Ok(PositionInformation {
+            entry_price: number_field(value, "avgPrice").unwrap_or_default(),
+            free_qty: size,
+            is_isolated: number_field(value, "tradeMode").unwrap_or_default().to_i32().unwrap_or_default() == 1,
+            leverage: number_field(value, "leverage").unwrap_or_default().to_i32().unwrap_or_default(),
+            liq_price: number_field(value, "liqPrice").unwrap_or_default(),
+            side,
+            size,
+            symbol: value.get("symbol").and_then(|s| s.as_str()).unwrap_or_default().to_string(),
+            unrealised_pnl: number_field(value, "unrealisedPnl").unwrap_or_default(),
+        })
+    }
+}
+
+pub fn get_time_in_force(time_in_force: &TimeInForce) -> String {
+    match time_in_force {
+        TimeInForce::GoodTillCancel => String::from("GTC"),
+        TimeInForce::ImmediateOrCancel => String::from("IOC"),
+        TimeInForce::FillOrKill => String::from("FOK"),
+        TimeInForce::PostOnly => String::from("PostOnly"),
+    }
+}
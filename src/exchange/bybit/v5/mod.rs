@@ -0,0 +1,327 @@
+use async_trait::async_trait;
+use log::warn;
+use reqwest::Client;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use ring::hmac;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use rustc_serialize::hex::ToHex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::common::environments::bybit_recv_window;
+use crate::common::utils::get_current_timestamp;
+use crate::exchange::account::{AccountConfig, Credentials};
+use crate::exchange::bybit::symbols::{self, SymbolInfo, SymbolList};
+use crate::exchange::bybit::v5::market_structs::*;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{MarketApi, number_field};
+use crate::exchange::structs::*;
+
+pub(super) mod market_structs;
+
+/// Bybit V5 client for unified trading accounts.
+pub struct Market {
+    client: Client,
+    credentials: Credentials,
+    base_url: &'static str,
+}
+
+const MAIN_BASE_URL: &str = "https://api.bybit.com";
+const BASE_URL_TESTNET: &str = "https://api-testnet.bybit.com";
+
+const ORDER_PATH: &str = "/v5/order/create";
+const POSITION_LIST_PATH: &str = "/v5/position/list";
+const WALLET_BALANCE_PATH: &str = "/v5/account/wallet-balance";
+const LEVERAGE_PATH: &str = "/v5/position/set-leverage";
+const MARGIN_MODE_PATH: &str = "/v5/account/set-margin-mode";
+const ACCOUNT_INFO_PATH: &str = "/v5/account/info";
+const TRADING_STOP_PATH: &str = "/v5/position/trading-stop";
+const CLOSED_PNL_PATH: &str = "/v5/position/closed-pnl";
+pub(super) const CANCEL_ORDER_PATH: &str = "/v5/order/cancel";
+pub(super) const CANCEL_ALL_PATH: &str = "/v5/order/cancel-all";
+pub(super) const AMEND_ORDER_PATH: &str = "/v5/order/amend";
+pub(super) const OPEN_ORDERS_PATH: &str = "/v5/order/realtime";
+
+const LEVERAGE_NOT_MODIFIED: i64 = 110043;
+const CATEGORY_LINEAR: &str = "linear";
+const CATEGORY_INVERSE: &str = "inverse";
+
+impl Market {
+    pub fn new(account: &AccountConfig, client: Client) -> Self {
+        Market {
+            client,
+            credentials: account.credentials.clone(),
+            base_url: if account.testnet { BASE_URL_TESTNET } else { MAIN_BASE_URL },
+        }
+    }
+
+    async fn get<T: Serialize>(&self, api_path: &str, request: &T) -> ExchangeResult<Value> {
+        let query = serde_urlencoded::to_string(request).map_err(|e| ExchangeError::decode(e.to_string()))?;
+        call_api(&self.client, self.base_url, api_path, HttpMethod::Get, query, &self.credentials).await?.into_result()
+    }
+
+    async fn post<T: Serialize>(&self, api_path: &str, request: &T) -> ExchangeResult<Value> {
+        let body = serde_json::to_string(request)?;
+        call_api(&self.client, self.base_url, api_path, HttpMethod::Post, body, &self.credentials).await?.into_result()
+    }
+
+    async fn symbol_info(&self, symbol: &str) -> ExchangeResult<SymbolInfo> {
+        symbols::symbol_info(&self.client, self.base_url, SymbolList::V5(category(symbol)), symbol).await
+    }
+}
+
+/// Orders in the `list` of a V5 order query.
+pub(super) fn orders_from_list(result: &Value) -> ExchangeResult<Vec<OrderInfo>> {
+    result.get("list").and_then(|list| list.as_array()).map(|orders| orders.as_slice()).unwrap_or_default()
+        .iter()
+        .map(OrderInfo::from_v5)
+        .collect()
+}
+
+/// V5 serves inverse perpetuals and inverse futures under the same category.
+fn category(symbol: &str) -> &'static str {
+    if ContractType::from_symbol(symbol).is_inverse() { CATEGORY_INVERSE } else { CATEGORY_LINEAR }
+}
+
+#[async_trait]
+impl MarketApi for Market {
+    fn contract_type(&self, symbol: &str) -> ContractType {
+        ContractType::from_symbol(symbol)
+    }
+
+    fn tracks_orders(&self) -> bool {
+        true
+    }
+
+    fn trails_stops(&self) -> bool {
+        true
+    }
+
+    fn triggers_by(&self, _trigger_by: TriggerBy) -> bool {
+        true
+    }
+
+    async fn order(&self, order: Order) -> ExchangeResult<OrderAck> {
+        let order = self.symbol_info(&order.symbol).await?.round_order(order)?;
+        let request = OrderRequest::new(category(&order.symbol), &order);
+        let result = self.post(ORDER_PATH, &request).await?;
+        OrderAck::from_v5(&result)
+    }
+
+    async fn take_profit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, _stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        let price = take_profit.ok_or_else(|| ExchangeError::InvalidOrder(format!("Take profit for {} has no price", symbol)))?;
+        let qty = qty.ok_or_else(|| ExchangeError::InvalidOrder(format!("Take profit for {} has no qty", symbol)))?;
+        let take_profit_limit = Order {
+            symbol: symbol.to_string(),
+            time_in_force: TimeInForce::PostOnly,
+            price,
+            qty,
+            reduce_only: Some(true),
+            close_on_trigger: Some(true),
+            order_type: OrderType::Limit,
+            leverage: Option::None,
+            side: side.opposite(),
+            take_profit: None,
+            stop_loss: None,
+            order_link_id: options.order_link_id.map(String::from),
+        };
+        self.order(take_profit_limit).await?;
+        Ok(())
+    }
+
+    async fn stop_loss(&self, symbol: &str, _qty: Option<Decimal>, _side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        let info = self.symbol_info(symbol).await?;
+        let trigger_by = options.trigger_by.unwrap_or(TriggerBy::Last);
+        let request = TradingStop::new(category(symbol), symbol, take_profit.map(|p| info.round_price(p)), stop_loss.map(|p| info.round_price(p)), trigger_by);
+        self.post(TRADING_STOP_PATH, &request).await?;
+        Ok(())
+    }
+
+    async fn position(&self, symbol: &str) -> ExchangeResult<Option<PositionInformation>> {
+        let result = self.get(POSITION_LIST_PATH, &PositionRequest::new(category(symbol), symbol)).await?;
+        let positions = result.get("list").and_then(|list| list.as_array()).cloned().unwrap_or_default();
+        // In hedge mode both sides are listed, prefer the one holding a position.
+        let position = positions.iter()
+            .find(|p| number_field(p, "size").map(|size| size > Decimal::ZERO).unwrap_or(false))
+            .or_else(|| positions.first());
+        match position {
+            Some(value) => Ok(Some(PositionInformation::from_v5(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Linear positions settled in USDT and USDC and inverse positions.
+    async fn positions(&self) -> ExchangeResult<Vec<PositionInformation>> {
+        let requests = [
+            PositionRequest::settled_in(CATEGORY_LINEAR, "USDT"),
+            PositionRequest::settled_in(CATEGORY_LINEAR, "USDC"),
+            PositionRequest::all(CATEGORY_INVERSE),
+        ];
+        let mut positions = Vec::new();
+        for request in requests.iter() {
+            let result = self.get(POSITION_LIST_PATH, request).await?;
+            for value in result.get("list").and_then(|list| list.as_array()).map(|list| list.as_slice()).unwrap_or_default() {
+                if number_field(value, "size").map(|size| size > Decimal::ZERO).unwrap_or(false) {
+                    positions.push(PositionInformation::from_v5(value)?);
+                }
+            }
+        }
+        Ok(positions)
+    }
+
+    async fn is_in_position(&self, symbol: &str) -> ExchangeResult<bool> {
+        Ok(match self.position(symbol).await? {
+            Some(position_info) => position_info.size > Decimal::ZERO,
+            None => false,
+        })
+    }
+
+    async fn wallet_available_balance(&self, coin: &str) -> ExchangeResult<Decimal> {
+        let result = self.get(WALLET_BALANCE_PATH, &WalletBalanceRequest::new(coin)).await?;
+        let account = result.get("list").and_then(|list| list.get(0))
+            .ok_or_else(|| ExchangeError::decode("wallet balance list is empty"))?;
+        let coin_balance = account.get("coin")
+            .and_then(|coins| coins.as_array())
+            .and_then(|coins| coins.iter().find(|c| c.get("coin").and_then(|n| n.as_str()) == Some(coin)));
+        match coin_balance {
+            Some(balance) => number_field(balance, "availableToWithdraw").or_else(|_| number_field(balance, "walletBalance")),
+            None => Ok(Decimal::ZERO),
+        }
+    }
+
+    async fn leverage(&self, symbol: &str, leverage: i32) -> ExchangeResult<()> {
+        let leverage = leverage.min(self.symbol_info(symbol).await?.max_leverage);
+        let request = LeverageRequest::new(category(symbol), symbol, leverage);
+        match self.post(LEVERAGE_PATH, &request).await {
+            Err(ExchangeError::Exchange { code: LEVERAGE_NOT_MODIFIED, .. }) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    async fn switch_isolated(&self, _symbol: &str, isolated: bool, _leverage: i32) -> ExchangeResult<()> {
+        // Unified accounts set the margin mode for the whole account, it is only changed when it differs.
+        let request = MarginModeRequest::new(isolated);
+        let account = self.get(ACCOUNT_INFO_PATH, &()).await?;
+        if account.get("marginMode").and_then(|mode| mode.as_str()) == Some(request._set_margin_mode.as_str()) {
+            return Ok(());
+        }
+        match self.post(MARGIN_MODE_PATH, &request).await {
+            // Refused while positions or orders are open, the entry keeps the current mode.
+            Err(ExchangeError::Exchange { code, message }) => {
+                warn!("Margin mode left unchanged: {} {}", code, message);
+                Ok(())
+            }
+            result => result.map(|_| ()),
+        }
+    }
+
+    async fn trailing_stop(&self, symbol: &str, _side: &OrderSide, trailing_stop: &TrailingStop) -> ExchangeResult<()> {
+        let info = self.symbol_info(symbol).await?;
+        let trailing_stop = TrailingStop {
+            distance: info.round_price(trailing_stop.distance),
+            active_price: trailing_stop.active_price.map(|p| info.round_price(p)),
+        };
+        self.post(TRADING_STOP_PATH, &TradingStop::trailing(category(symbol), symbol, &trailing_stop)).await?;
+        Ok(())
+    }
+
+    async fn qty_step(&self, symbol: &str) -> ExchangeResult<Option<Decimal>> {
+        Ok(Some(self.symbol_info(symbol).await?.qty_step))
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: &str) -> ExchangeResult<()> {
+        self.post(CANCEL_ORDER_PATH, &CancelOrderRequest::new(category(symbol), symbol, order_id)).await?;
+        Ok(())
+    }
+
+    async fn cancel_all(&self, symbol: &str) -> ExchangeResult<()> {
+        self.post(CANCEL_ALL_PATH, &CancelAllRequest::new(category(symbol), symbol, None)).await?;
+        Ok(())
+    }
+
+    async fn amend_order(&self, symbol: &str, order_id: &str, price: Option<Decimal>, qty: Option<Decimal>) -> ExchangeResult<()> {
+        let info = self.symbol_info(symbol).await?;
+        let qty = match qty {
+            Some(qty) => Some(info.round_qty(symbol, qty)?),
+            None => None,
+        };
+        self.post(AMEND_ORDER_PATH, &AmendOrderRequest::new(category(symbol), symbol, order_id, price.map(|p| info.round_price(p)), qty)).await?;
+        Ok(())
+    }
+
+    async fn order_status(&self, symbol: &str, order_id: &str) -> ExchangeResult<OrderInfo> {
+        let result = self.get(OPEN_ORDERS_PATH, &OrderQueryRequest::by_id(category(symbol), symbol, order_id)).await?;
+        let order = result.get("list").and_then(|list| list.get(0))
+            .ok_or_else(|| ExchangeError::Exchange { code: -1, message: format!("Order {} not found", order_id) })?;
+        OrderInfo::from_v5(order)
+    }
+
+    async fn open_orders(&self, symbol: &str) -> ExchangeResult<Vec<OrderInfo>> {
+        let result = self.get(OPEN_ORDERS_PATH, &OrderQueryRequest::open(category(symbol), symbol, None)).await?;
+        orders_from_list(&result)
+    }
+
+    /// Every symbol is listed from the linear category, a page at a time.
+    async fn closed_pnl(&self, symbol: Option<&str>, start_time: i64) -> ExchangeResult<Vec<ClosedPnl>> {
+        let mut request = ClosedPnlRequest::new(symbol.map_or(CATEGORY_LINEAR, category), symbol, start_time);
+        let mut closed = Vec::new();
+        loop {
+            let result = self.get(CLOSED_PNL_PATH, &request).await?;
+            for value in result.get("list").and_then(|list| list.as_array()).map(|list| list.as_slice()).unwrap_or_default() {
+                closed.push(ClosedPnl {
+                    symbol: value.get("symbol").and_then(|s| s.as_str()).unwrap_or_default().to_string(),
+                    pnl: number_field(value, "closedPnl")?,
+                    closed_at: number_field(value, "updatedTime")?.to_i64().unwrap_or_default(),
+                });
+            }
+            request._cursor = match result.get("nextPageCursor").and_then(|cursor| cursor.as_str()) {
+                Some(cursor) if !cursor.is_empty() => Some(cursor.to_string()),
+                _ => return Ok(closed),
+            };
+        }
+    }
+
+    async fn find_order(&self, symbol: &str, order_link_id: &str) -> ExchangeResult<Option<OrderInfo>> {
+        let result = self.get(OPEN_ORDERS_PATH, &OrderQueryRequest::by_link_id(category(symbol), symbol, order_link_id)).await?;
+        Ok(orders_from_list(&result)?.into_iter().next())
+    }
+}
+
+pub(super) async fn call_api(client: &Client, base_url: &str, api_path: &str, method: HttpMethod, payload: String, credentials: &Credentials) -> ExchangeResult<ApiResponse> {
+    let timestamp = get_current_timestamp().to_string();
+    let recv_window = bybit_recv_window().to_string();
+
+    // Sign timestamp + api key + recv window + (query string | json body)
+    let key = hmac::Key::new(hmac::HMAC_SHA256, credentials.api_secret.as_bytes());
+    let string_to_sign = format!("{}{}{}{}", timestamp, credentials.api_key, recv_window, payload);
+    let sign = hmac::sign(&key, string_to_sign.as_bytes()).as_ref().to_hex();
+
+    let auth_error = |e: reqwest::header::InvalidHeaderValue| ExchangeError::Auth { code: -1, message: e.to_string() };
+
+    // Set Headers
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.insert("X-BAPI-API-KEY", HeaderValue::from_str(&credentials.api_key).map_err(auth_error)?);
+    headers.insert("X-BAPI-SIGN", HeaderValue::from_str(&sign).map_err(auth_error)?);
+    headers.insert("X-BAPI-TIMESTAMP", HeaderValue::from_str(&timestamp).map_err(auth_error)?);
+    headers.insert("X-BAPI-RECV-WINDOW", HeaderValue::from_str(&recv_window).map_err(auth_error)?);
+
+    // Send request
+    let url = format!("{}{}", base_url, api_path);
+    let resp = match method {
+        HttpMethod::Get => client.get(format!("{}?{}", url, payload)).headers(headers).send(),
+        HttpMethod::Delete => client.delete(format!("{}?{}", url, payload)).headers(headers).send(),
+        HttpMethod::Post => client.post(url).headers(headers).body(payload).send(),
+        HttpMethod::Put => client.put(url).headers(headers).body(payload).send(),
+    }.await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if status.as_u16() == 403 || status.as_u16() == 429 {
+        return Err(ExchangeError::RateLimited { code: status.as_u16() as i64, message: body });
+    }
+    if !status.is_success() {
+        return Err(ExchangeError::HttpStatus { status: status.as_u16(), body });
+    }
+    Ok(serde_json::from_str::<ApiResponse>(&body)?)
+}
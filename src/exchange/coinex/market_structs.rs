@@ -0,0 +1,408 @@
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::utils::get_current_timestamp;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{id_field, number_field};
+use crate::exchange::structs::{OrderAck, OrderInfo, OrderSide, OrderStatus, OrderType, PositionInformation, TimeInForce, TriggerBy};
+
+pub const POSITION_TYPE_ISOLATED: i8 = 1;
+pub const POSITION_TYPE_CROSS: i8 = 2;
+
+/// Most orders returned by one order list request.
+const ORDER_LIST_LIMIT: i64 = 100;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AdjustLeverage {
+    #[serde(rename = "market")]
+    pub _market: String,
+    #[serde(rename = "leverage")]
+    pub _leverage: i32,
+    #[serde(rename = "position_type")]
+    pub _position_type: i8,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PutLimitRequest {
+    #[serde(rename = "market")]
+    pub _market: String,
+    #[serde(rename = "side")]
+    pub _side: i8,
+    #[serde(rename = "price")]
+    pub _price: Decimal,
+    #[serde(rename = "amount")]
+    pub _amount: Decimal,
+    #[serde(rename = "effect_type")]
+    pub _effect_type: i8,
+    #[serde(rename = "option")]
+    pub _option: i8,
+    #[serde(rename = "client_id", skip_serializing_if = "Option::is_none")]
+    pub _client_id: Option<String>,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PutMarketRequest {
+    #[serde(rename = "market")]
+    pub _market: String,
+    #[serde(rename = "side")]
+    pub _side: i8,
+    #[serde(rename = "amount")]
+    pub _amount: Decimal,
+    #[serde(rename = "client_id", skip_serializing_if = "Option::is_none")]
+    pub _client_id: Option<String>,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CloseLimitRequest {
+    #[serde(rename = "market")]
+    pub _market: String,
+    #[serde(rename = "position_id")]
+    pub _position_id: i64,
+    #[serde(rename = "price")]
+    pub _price: Decimal,
+    #[serde(rename = "amount")]
+    pub _amount: Decimal,
+    #[serde(rename = "effect_type")]
+    pub _effect_type: i8,
+    #[serde(rename = "option")]
+    pub _option: i8,
+    #[serde(rename = "client_id", skip_serializing_if = "Option::is_none")]
+    pub _client_id: Option<String>,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CloseMarketRequest {
+    #[serde(rename = "market")]
+    pub _market: String,
+    #[serde(rename = "position_id")]
+    pub _position_id: i64,
+    #[serde(rename = "amount")]
+    pub _amount: Decimal,
+    #[serde(rename = "client_id", skip_serializing_if = "Option::is_none")]
+    pub _client_id: Option<String>,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderListRequest {
+    #[serde(rename = "market")]
+    pub _market: String,
+    #[serde(rename = "side")]
+    pub _side: i8,
+    #[serde(rename = "offset")]
+    pub _offset: i64,
+    #[serde(rename = "limit")]
+    pub _limit: i64,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelAllRequest {
+    #[serde(rename = "market")]
+    pub _market: String,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PendingPositionRequest {
+    #[serde(rename = "market", skip_serializing_if = "Option::is_none")]
+    pub _market: Option<String>,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PositionStopRequest {
+    #[serde(rename = "market")]
+    pub _market: String,
+    #[serde(rename = "position_id")]
+    pub _position_id: i64,
+    #[serde(rename = "stop_type")]
+    pub _stop_type: i8,
+    #[serde(rename = "stop_loss_price", skip_serializing_if = "Option::is_none")]
+    pub _stop_loss_price: Option<Decimal>,
+    #[serde(rename = "take_profit_price", skip_serializing_if = "Option::is_none")]
+    pub _take_profit_price: Option<Decimal>,
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssetQueryRequest {
+    #[serde(rename = "timestamp")]
+    pub _timestamp: i64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApiResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub data: Value,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl OrderAck {
+    /// Orders are reported with the `amount` asked and what is `left` to fill.
+    pub fn from_coinex(value: &Value) -> ExchangeResult<OrderAck> {
+        let amount = number_field(value, "amount").unwrap_or_default();
+        let left = number_field(value, "left").unwrap_or(amount);
+        let executed_qty = amount - left;
+        let deal_value = number_field(value, "deal_stock").unwrap_or_default();
+        let status = if amount > Decimal::ZERO && left <= Decimal::ZERO {
+            OrderStatus::Filled
+        } else if executed_qty > Decimal::ZERO {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::New
+        };
+
+        Ok(OrderAck {
+            order_id: id_field(value, "order_id")?,
+            order_link_id: value.get("client_id").and_then(|id| id.as_str()).unwrap_or_default().to_string(),
+            status,
+            avg_price: if executed_qty > Decimal::ZERO && deal_value > Decimal::ZERO { Some(deal_value / executed_qty) } else { None },
+            executed_qty: Some(executed_qty),
+        })
+    }
+}
+
+impl OrderInfo {
+    /// Orders are listed with the `amount` asked and what is `left`, a `finished` order left
+    /// unfilled was cancelled.
+    pub fn from_coinex(value: &Value, finished: bool) -> ExchangeResult<OrderInfo> {
+        let ack = OrderAck::from_coinex(value)?;
+        let executed_qty = ack.executed_qty.unwrap_or_default();
+        let status = match ack.status {
+            OrderStatus::Filled => OrderStatus::Filled,
+            _ if finished => OrderStatus::Cancelled,
+            status => status,
+        };
+        Ok(OrderInfo {
+            order_id: ack.order_id,
+            order_link_id: ack.order_link_id,
+            symbol: value.get("market").and_then(|m| m.as_str()).unwrap_or_default().to_string(),
+            side: if value.get("side").and_then(|s| s.as_i64()) == Some(1) { OrderSide::Short } else { OrderSide::Long },
+            order_type: if value.get("type").and_then(|t| t.as_i64()) == Some(2) { OrderType::Market } else { OrderType::Limit },
+            status,
+            price: number_field(value, "price").unwrap_or_default(),
+            qty: number_field(value, "amount").unwrap_or_default(),
+            executed_qty,
+            avg_price: ack.avg_price.unwrap_or_default(),
+            trigger_price: None,
+            reduce_only: false,
+        })
+    }
+}
+
+impl ApiResponse {
+    pub fn into_result(self) -> ExchangeResult<Value> {
+        let code = self.code;
+        let message = self.message;
+        match code {
+            0 => Ok(self.data),
+            23 | 24 | 25 | 34 => Err(ExchangeError::Auth { code, message }),
+            213 => Err(ExchangeError::RateLimited { code, message }),
+            107 => Err(ExchangeError::InsufficientBalance { code, message }),
+            _ => Err(ExchangeError::Exchange { code, message }),
+        }
+    }
+}
+
+//------------- Change Leverage -------------
+impl AdjustLeverage {
+    pub fn new(market: &str, leverage: i32, position_type: i8) -> Self {
+        AdjustLeverage {
+            _market: market.into(),
+            _leverage: leverage,
+            _position_type: position_type,
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+//------------- Put Limit Request -------------
+impl PutLimitRequest {
+    pub fn new(market: &str, side: &OrderSide, price: Decimal, amount: Decimal, time_in_force: &TimeInForce, client_id: Option<&str>) -> Self {
+        PutLimitRequest {
+            _market: market.into(),
+            _side: get_side(side),
+            _price: price,
+            _amount: amount,
+            _effect_type: get_effect_type(time_in_force),
+            _option: get_option(time_in_force),
+            _client_id: client_id.map(String::from),
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+//------------- Put Market Request -------------
+impl PutMarketRequest {
+    pub fn new(market: &str, side: &OrderSide, amount: Decimal, client_id: Option<&str>) -> Self {
+        PutMarketRequest {
+            _market: market.into(),
+            _side: get_side(side),
+            _amount: amount,
+            _client_id: client_id.map(String::from),
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+//------------- Close Limit Request -------------
+impl CloseLimitRequest {
+    pub fn new(market: &str, position_id: i64, price: Decimal, amount: Decimal, time_in_force: &TimeInForce, client_id: Option<&str>) -> Self {
+        CloseLimitRequest {
+            _market: market.into(),
+            _position_id: position_id,
+            _price: price,
+            _amount: amount,
+            _effect_type: get_effect_type(time_in_force),
+            _option: get_option(time_in_force),
+            _client_id: client_id.map(String::from),
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+//------------- Close Market Request -------------
+impl CloseMarketRequest {
+    pub fn new(market: &str, position_id: i64, amount: Decimal, client_id: Option<&str>) -> Self {
+        CloseMarketRequest {
+            _market: market.into(),
+            _position_id: position_id,
+            _amount: amount,
+            _client_id: client_id.map(String::from),
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+impl OrderListRequest {
+    /// The latest orders on `market`, both sides.
+    pub fn new(market: &str) -> Self {
+        OrderListRequest {
+            _market: market.into(),
+            _side: 0,
+            _offset: 0,
+            _limit: ORDER_LIST_LIMIT,
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+impl CancelAllRequest {
+    pub fn new(market: &str) -> Self {
+        CancelAllRequest {
+            _market: market.into(),
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+impl PendingPositionRequest {
+    pub fn new(market: &str) -> Self {
+        PendingPositionRequest {
+            _market: Some(market.into()),
+            _timestamp: get_current_timestamp(),
+        }
+    }
+
+    /// Positions on every market.
+    pub fn all() -> Self {
+        PendingPositionRequest {
+            _market: None,
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+impl PositionStopRequest {
+    pub fn new(market: &str, position_id: i64, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, trigger_by: TriggerBy) -> Self {
+        PositionStopRequest {
+            _market: market.into(),
+            _position_id: position_id,
+            _stop_type: get_stop_type(trigger_by),
+            _stop_loss_price: stop_loss,
+            _take_profit_price: take_profit,
+            _timestamp: get_current_timestamp(),
+        }
+    }
+}
+
+impl Default for AssetQueryRequest {
+    fn default() -> Self {
+        AssetQueryRequest { _timestamp: get_current_timestamp() }
+    }
+}
+
+/// A pending CoinEx position together with the id needed to close it.
+pub struct PendingPosition {
+    pub position_id: i64,
+    pub information: PositionInformation,
+}
+
+impl PendingPosition {
+    pub fn from_value(value: &Value) -> ExchangeResult<PendingPosition> {
+        let side = if number_field(value, "side")?.to_i64().unwrap_or_default() == 2 { OrderSide::Long } else { OrderSide::Short };
+
+        Ok(PendingPosition {
+            position_id: number_field(value, "position_id")?.to_i64().unwrap_or_default(),
+            information: PositionInformation {
+                entry_price: number_field(value, "open_price")?,
+                free_qty: number_field(value, "close_left")?,
+                is_isolated: number_field(value, "type")?.to_i8().unwrap_or_default() == POSITION_TYPE_ISOLATED,
+                leverage: number_field(value, "leverage")?.to_i32().unwrap_or_default(),
+                liq_price: number_field(value, "liq_price")?,
+                side,
+                size: number_field(value, "amount")?,
+                symbol: value.get("market").and_then(|m| m.as_str()).unwrap_or_default().to_string(),
+                unrealised_pnl: number_field(value, "profit_unreal").unwrap_or_default(),
+            },
+        })
+    }
+}
+
+fn get_side(side: &OrderSide) -> i8 {
+    match side {
+        OrderSide::Short => 1,
+        OrderSide::Long => 2,
+    }
+}
+
+fn get_effect_type(time_in_force: &TimeInForce) -> i8 {
+    match time_in_force {
+        TimeInForce::GoodTillCancel | TimeInForce::PostOnly => 1,
+        TimeInForce::ImmediateOrCancel => 2,
+        TimeInForce::FillOrKill => 3,
+    }
+}
+
+fn get_stop_type(trigger_by: TriggerBy) -> i8 {
+    match trigger_by {
+        TriggerBy::Last => 1,
+        TriggerBy::Mark => 2,
+        TriggerBy::Index => 3,
+    }
+}
+
+fn get_option(time_in_force: &TimeInForce) -> i8 {
+    match time_in_force {
+        TimeInForce::PostOnly => 1,
+        _ => 0,
+    }
+}
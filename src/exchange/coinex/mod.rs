@@ -0,0 +1,219 @@
+use std::sync::atomic::{AtomicI8, Ordering};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+use sha256::digest;
+
+use crate::common::environments::coinex_position_type;
+use crate::exchange::account::{AccountConfig, Credentials};
+use crate::exchange::coinex::market_structs::*;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{MarketApi, number_field};
+use crate::exchange::structs::*;
+
+mod market_structs;
+
+pub struct Market {
+    client: Client,
+    credentials: Credentials,
+    position_type: AtomicI8,
+}
+
+const BASE_URL: &str = "https://api.coinex.com/perpetual/v1";
+
+const ADJUST_LEVERAGE_PATH: &str = "market/adjust_leverage";
+const PUT_LIMIT_PATH: &str = "order/put_limit";
+const PUT_MARKET_PATH: &str = "order/put_market";
+const CLOSE_LIMIT_PATH: &str = "order/close_limit";
+const CLOSE_MARKET_PATH: &str = "order/close_market";
+const PENDING_POSITION_PATH: &str = "position/pending";
+const STOP_LOSS_PATH: &str = "position/stop_loss";
+const TAKE_PROFIT_PATH: &str = "position/take_profit";
+const ASSET_QUERY_PATH: &str = "asset/query";
+const CANCEL_ALL_PATH: &str = "order/cancel_all";
+const PENDING_ORDERS_PATH: &str = "order/pending";
+const FINISHED_ORDERS_PATH: &str = "order/finished";
+const CANCEL_STOP_ALL_PATH: &str = "order/cancel_stop_all";
+
+impl Market {
+    pub fn new(account: &AccountConfig, client: Client) -> Self {
+        Market {
+            client,
+            credentials: account.credentials.clone(),
+            position_type: AtomicI8::new(coinex_position_type()),
+        }
+    }
+
+    async fn call_api<T: Serialize>(&self, request: &T, api_path: &str, method: HttpMethod) -> ExchangeResult<Value> {
+        let params = serde_urlencoded::to_string(request).map_err(|e| ExchangeError::decode(e.to_string()))?;
+        call_api(&self.client, params, api_path, method, &self.credentials).await?.into_result()
+    }
+
+    async fn pending_position(&self, symbol: &str) -> ExchangeResult<Option<PendingPosition>> {
+        let request = PendingPositionRequest::new(symbol);
+        let result = self.call_api(&request, PENDING_POSITION_PATH, HttpMethod::Get).await?;
+        match result.as_array().and_then(|positions| positions.first()) {
+            Some(value) => Ok(Some(PendingPosition::from_value(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn open_position(&self, symbol: &str) -> ExchangeResult<PendingPosition> {
+        self.pending_position(symbol).await?
+            .ok_or_else(|| ExchangeError::Exchange { code: -1, message: format!("No open position for {}", symbol) })
+    }
+
+    async fn adjust_leverage(&self, symbol: &str, leverage: i32, position_type: i8) -> ExchangeResult<()> {
+        let request = AdjustLeverage::new(symbol, leverage, position_type);
+        self.call_api(&request, ADJUST_LEVERAGE_PATH, HttpMethod::Post).await?;
+        self.position_type.store(position_type, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MarketApi for Market {
+    fn triggers_by(&self, _trigger_by: TriggerBy) -> bool {
+        true
+    }
+
+    async fn order(&self, order: Order) -> ExchangeResult<OrderAck> {
+        let result = if order.reduce_only == Some(true) {
+            let position = self.open_position(&order.symbol).await?;
+            match order.order_type {
+                OrderType::Limit => {
+                    let request = CloseLimitRequest::new(&order.symbol, position.position_id, order.price, order.qty, &order.time_in_force, order.order_link_id.as_deref());
+                    self.call_api(&request, CLOSE_LIMIT_PATH, HttpMethod::Post).await?
+                }
+                OrderType::Market => {
+                    let request = CloseMarketRequest::new(&order.symbol, position.position_id, order.qty, order.order_link_id.as_deref());
+                    self.call_api(&request, CLOSE_MARKET_PATH, HttpMethod::Post).await?
+                }
+            }
+        } else {
+            match order.order_type {
+                OrderType::Limit => {
+                    let request = PutLimitRequest::new(&order.symbol, &order.side, order.price, order.qty, &order.time_in_force, order.order_link_id.as_deref());
+                    self.call_api(&request, PUT_LIMIT_PATH, HttpMethod::Post).await?
+                }
+                OrderType::Market => {
+                    let request = PutMarketRequest::new(&order.symbol, &order.side, order.qty, order.order_link_id.as_deref());
+                    self.call_api(&request, PUT_MARKET_PATH, HttpMethod::Post).await?
+                }
+            }
+        };
+        OrderAck::from_coinex(&result)
+    }
+
+    async fn take_profit(&self, symbol: &str, qty: Option<Decimal>, _side: &OrderSide, take_profit: Option<Decimal>, _stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        let price = take_profit.ok_or_else(|| ExchangeError::InvalidOrder(format!("Take profit for {} has no price", symbol)))?;
+        let position = self.open_position(symbol).await?;
+        // A limit close, it fills at its price whatever `trigger_by` asks for.
+        let request = CloseLimitRequest::new(symbol, position.position_id, price, qty.unwrap_or(position.information.size), &TimeInForce::PostOnly, options.order_link_id);
+        self.call_api(&request, CLOSE_LIMIT_PATH, HttpMethod::Post).await?;
+        Ok(())
+    }
+
+    async fn stop_loss(&self, symbol: &str, _qty: Option<Decimal>, _side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        let position = self.open_position(symbol).await?;
+        let trigger_by = options.trigger_by.unwrap_or(TriggerBy::Last);
+        if stop_loss.is_some() {
+            let request = PositionStopRequest::new(symbol, position.position_id, None, stop_loss, trigger_by);
+            self.call_api(&request, STOP_LOSS_PATH, HttpMethod::Post).await?;
+        }
+        if take_profit.is_some() {
+            let request = PositionStopRequest::new(symbol, position.position_id, take_profit, None, trigger_by);
+            self.call_api(&request, TAKE_PROFIT_PATH, HttpMethod::Post).await?;
+        }
+        Ok(())
+    }
+
+    async fn position(&self, symbol: &str) -> ExchangeResult<Option<PositionInformation>> {
+        Ok(self.pending_position(symbol).await?.map(|p| p.information))
+    }
+
+    async fn positions(&self) -> ExchangeResult<Vec<PositionInformation>> {
+        let result = self.call_api(&PendingPositionRequest::all(), PENDING_POSITION_PATH, HttpMethod::Get).await?;
+        result.as_array().map(|list| list.as_slice()).unwrap_or_default()
+            .iter()
+            .map(|value| Ok(PendingPosition::from_value(value)?.information))
+            .collect()
+    }
+
+    async fn is_in_position(&self, symbol: &str) -> ExchangeResult<bool> {
+        Ok(match self.position(symbol).await? {
+            Some(position_info) => position_info.entry_price > Decimal::ZERO,
+            None => false,
+        })
+    }
+
+    async fn wallet_available_balance(&self, coin: &str) -> ExchangeResult<Decimal> {
+        let result = self.call_api(&AssetQueryRequest::default(), ASSET_QUERY_PATH, HttpMethod::Get).await?;
+        match result.get(coin) {
+            Some(asset) => number_field(asset, "available"),
+            None => Ok(Decimal::ZERO),
+        }
+    }
+
+    async fn leverage(&self, symbol: &str, leverage: i32) -> ExchangeResult<()> {
+        let position_type = self.position_type.load(Ordering::SeqCst);
+        self.adjust_leverage(symbol, leverage, position_type).await
+    }
+
+    async fn switch_isolated(&self, symbol: &str, isolated: bool, leverage: i32) -> ExchangeResult<()> {
+        let position_type = if isolated { POSITION_TYPE_ISOLATED } else { POSITION_TYPE_CROSS };
+        self.adjust_leverage(symbol, leverage, position_type).await
+    }
+
+    async fn cancel_all(&self, symbol: &str) -> ExchangeResult<()> {
+        // Limit exits are orders, stop orders are cancelled separately.
+        self.call_api(&CancelAllRequest::new(symbol), CANCEL_ALL_PATH, HttpMethod::Post).await?;
+        self.call_api(&CancelAllRequest::new(symbol), CANCEL_STOP_ALL_PATH, HttpMethod::Post).await?;
+        Ok(())
+    }
+
+    async fn find_order(&self, symbol: &str, order_link_id: &str) -> ExchangeResult<Option<OrderInfo>> {
+        // Only the latest orders are searched, open ones first.
+        for (api_path, finished) in [(PENDING_ORDERS_PATH, false), (FINISHED_ORDERS_PATH, true)] {
+            let result = self.call_api(&OrderListRequest::new(symbol), api_path, HttpMethod::Get).await?;
+            let order = result.get("records").and_then(|records| records.as_array()).map(|list| list.as_slice()).unwrap_or_default()
+                .iter()
+                .find(|order| order.get("client_id").and_then(|id| id.as_str()) == Some(order_link_id));
+            if let Some(order) = order {
+                return Ok(Some(OrderInfo::from_coinex(order, finished)?));
+            }
+        }
+        Ok(None)
+    }
+}
+
+async fn call_api(client: &Client, params: String, api_path: &str, method: HttpMethod, credentials: &Credentials) -> ExchangeResult<ApiResponse> {
+    // Sign params with secret key
+    let sign = digest(format!("{}&secret_key={}", params, credentials.api_secret)).to_lowercase();
+
+    let url = format!("{}/{}", BASE_URL, api_path);
+
+    // Set Headers
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-www-form-urlencoded"));
+    headers.insert("AccessId", HeaderValue::from_str(&credentials.api_key).map_err(|e| ExchangeError::Auth { code: -1, message: e.to_string() })?);
+    headers.insert("Authorization", HeaderValue::from_str(&sign).map_err(|e| ExchangeError::Auth { code: -1, message: e.to_string() })?);
+
+    // Send request
+    let resp = match method {
+        HttpMethod::Get => client.get(format!("{}?{}", url, params)).headers(headers).send(),
+        HttpMethod::Delete => client.delete(format!("{}?{}", url, params)).headers(headers).send(),
+        HttpMethod::Post => client.post(url).headers(headers).body(params).send(),
+        HttpMethod::Put => client.put(url).headers(headers).body(params).send(),
+    }.await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if !status.is_success() {
+        return Err(ExchangeError::HttpStatus { status: status.as_u16(), body });
+    }
+    Ok(serde_json::from_str::<ApiResponse>(&body)?)
+}
@@ -0,0 +1,72 @@
+use std::fmt::Formatter;
+
+pub type ExchangeResult<T> = Result<T, ExchangeError>;
+
+#[derive(Debug)]
+pub enum ExchangeError {
+    /// The request never got a response (connect, TLS, timeout, ...).
+    Transport(String),
+    /// The exchange answered with a non-success HTTP status.
+    HttpStatus { status: u16, body: String },
+    /// The response body could not be parsed into the expected shape.
+    Decode(String),
+    /// The key, secret or signature was rejected.
+    Auth { code: i64, message: String },
+    /// Too many requests, the call may be retried later.
+    RateLimited { code: i64, message: String },
+    /// Not enough margin or balance to place the order.
+    InsufficientBalance { code: i64, message: String },
+    /// Any other business error returned by the exchange.
+    Exchange { code: i64, message: String },
+    /// The connector does not implement the operation.
+    Unsupported(String),
+    /// The order breaks the symbol's trading rules and was not sent.
+    InvalidOrder(String),
+}
+
+impl ExchangeError {
+    pub fn decode(message: impl Into<String>) -> Self {
+        ExchangeError::Decode(message.into())
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ExchangeError::Transport(_) | ExchangeError::RateLimited { .. })
+            || matches!(self, ExchangeError::HttpStatus { status, .. } if *status >= 500)
+    }
+}
+
+impl std::fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExchangeError::Transport(message) => write!(f, "Transport error: {}", message),
+            ExchangeError::HttpStatus { status, body } => write!(f, "HTTP status {}: {}", status, body),
+            ExchangeError::Decode(message) => write!(f, "Decode error: {}", message),
+            ExchangeError::Auth { code, message } => write!(f, "Authentication error {}: {}", code, message),
+            ExchangeError::RateLimited { code, message } => write!(f, "Rate limited {}: {}", code, message),
+            ExchangeError::InsufficientBalance { code, message } => write!(f, "Insufficient balance {}: {}", code, message),
+            ExchangeError::Exchange { code, message } => write!(f, "Exchange error {}: {}", code, message),
+            ExchangeError::Unsupported(operation) => write!(f, "Unsupported operation: {}", operation),
+            ExchangeError::InvalidOrder(message) => write!(f, "Invalid order: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ExchangeError {}
+
+impl From<reqwest::Error> for ExchangeError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            ExchangeError::Decode(e.to_string())
+        } else if let Some(status) = e.status() {
+            ExchangeError::HttpStatus { status: status.as_u16(), body: e.to_string() }
+        } else {
+            ExchangeError::Transport(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ExchangeError {
+    fn from(e: serde_json::Error) -> Self {
+        ExchangeError::Decode(e.to_string())
+    }
+}
@@ -1,19 +1,145 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use rust_decimal::Decimal;
 use serde_json::Value;
 
-use crate::exchange::structs::{OrderSide, PositionInformation};
+use crate::exchange::account::{AccountConfig, AccountType, Exchange};
+use crate::exchange::{binance, bybit, coinex, kucoin, okx};
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::structs::{ClosedPnl, ContractType, ExitOptions, MarketType, OrderAck, OrderInfo, OrderSide, PositionInformation, TrailingStop, TriggerBy};
 
 use super::structs::Order;
 
-pub trait MarketApi {
-    fn order(order: Order, metadata: &Value) -> bool;
-    fn take_profit(symbol: &String, qty: Option<f64>, side: &OrderSide, take_profit: Option<f64>, stop_loss: Option<f64>, metadata: &Value) -> bool;
-    fn stop_loss(symbol: &String, qty: Option<f64>, side: &OrderSide, take_profit: Option<f64>, stop_loss: Option<f64>, metadata: &Value) -> bool;
-    fn position(symbol: &String, metadata: &Value) -> Option<PositionInformation>;
-    fn is_in_position(symbol: &String, metadata: &Value) -> bool;
-    fn wallet_available_balance(coin: String, metadata: &Value) -> f64;
-    fn leverage(symbol: &String, leverage: i32, metadata: &Value) -> bool;
-    fn switch_isolated(symbol: &String, isolated: bool, leverage: i32, metadata: &Value) -> bool;
+#[async_trait]
+pub trait MarketApi: Send + Sync {
+    /// Contract type traded for `symbol`, connectors without inverse contracts only trade linear.
+    fn contract_type(&self, _symbol: &str) -> ContractType {
+        ContractType::Linear
+    }
+
+    /// Whether `order_status` and `cancel_order` are implemented, a limit entry is followed with both.
+    fn tracks_orders(&self) -> bool {
+        false
+    }
+
+    /// Whether `trailing_stop` is implemented.
+    fn trails_stops(&self) -> bool {
+        false
+    }
+
+    /// Whether exits can be triggered by `trigger_by`, the last price is the default everywhere.
+    fn triggers_by(&self, trigger_by: TriggerBy) -> bool {
+        trigger_by == TriggerBy::Last
+    }
+    async fn order(&self, order: Order) -> ExchangeResult<OrderAck>;
+    async fn take_profit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()>;
+    /// `order_link_id` names the stop order where the exchange places one, a take profit
+    /// set in the same call and position level stops are not named.
+    async fn stop_loss(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()>;
+    async fn position(&self, symbol: &str) -> ExchangeResult<Option<PositionInformation>>;
+    async fn is_in_position(&self, symbol: &str) -> ExchangeResult<bool>;
+    async fn wallet_available_balance(&self, coin: &str) -> ExchangeResult<Decimal>;
+    async fn leverage(&self, symbol: &str, leverage: i32) -> ExchangeResult<()>;
+    async fn switch_isolated(&self, symbol: &str, isolated: bool, leverage: i32) -> ExchangeResult<()>;
+
+    /// Open positions on every symbol of the product.
+    async fn positions(&self) -> ExchangeResult<Vec<PositionInformation>> {
+        Err(ExchangeError::Unsupported(String::from("positions")))
+    }
+
+    /// Positions closed on `symbol`, or on every USD settled symbol when `None`, since `start_time`
+    /// (ms since the epoch).
+    async fn closed_pnl(&self, _symbol: Option<&str>, _start_time: i64) -> ExchangeResult<Vec<ClosedPnl>> {
+        Err(ExchangeError::Unsupported(String::from("closed_pnl")))
+    }
+
+    /// Exchange side trailing stop on the position opened on `side`.
+    async fn trailing_stop(&self, _symbol: &str, _side: &OrderSide, _trailing_stop: &TrailingStop) -> ExchangeResult<()> {
+        Err(ExchangeError::Unsupported(String::from("trailing_stop")))
+    }
+
+    /// Smallest quantity increment of `symbol` in coins, `None` when the connector does not know it.
+    async fn qty_step(&self, _symbol: &str) -> ExchangeResult<Option<Decimal>> {
+        Ok(None)
+    }
+
+    async fn cancel_order(&self, _symbol: &str, _order_id: &str) -> ExchangeResult<()> {
+        Err(ExchangeError::Unsupported(String::from("cancel_order")))
+    }
+
+    /// Cancel every open order on `symbol`, including conditional ones.
+    async fn cancel_all(&self, _symbol: &str) -> ExchangeResult<()> {
+        Err(ExchangeError::Unsupported(String::from("cancel_all")))
+    }
+
+    /// Replace the price and/or quantity of an open order.
+    async fn amend_order(&self, _symbol: &str, _order_id: &str, _price: Option<Decimal>, _qty: Option<Decimal>) -> ExchangeResult<()> {
+        Err(ExchangeError::Unsupported(String::from("amend_order")))
+    }
+
+    async fn order_status(&self, _symbol: &str, _order_id: &str) -> ExchangeResult<OrderInfo> {
+        Err(ExchangeError::Unsupported(String::from("order_status")))
+    }
+
+    /// Open and conditional orders on `symbol`.
+    async fn open_orders(&self, _symbol: &str) -> ExchangeResult<Vec<OrderInfo>> {
+        Err(ExchangeError::Unsupported(String::from("open_orders")))
+    }
+
+    /// Look an order up by the client order ID it was placed with.
+    async fn find_order(&self, _symbol: &str, _order_link_id: &str) -> ExchangeResult<Option<OrderInfo>> {
+        Err(ExchangeError::Unsupported(String::from("find_order")))
+    }
+}
+
+/// Client for the product `market_type` on the account's exchange.
+pub fn market(account: &AccountConfig, market_type: MarketType, client: Client) -> Result<Box<dyn MarketApi>, String> {
+    if account.exchange != Exchange::Bybit && market_type != MarketType::Linear {
+        return Err(format!("{} is not supported on {}", market_type, account.exchange));
+    }
+    Ok(match account.exchange {
+        Exchange::Bybit if market_type.is_spot() => Box::new(bybit::spot::Market::new(account, client, market_type == MarketType::SpotMargin)),
+        Exchange::Bybit => match account.account_type {
+            AccountType::Classic => Box::new(bybit::Market::new(account, client)),
+            AccountType::Unified => Box::new(bybit::v5::Market::new(account, client)),
+        },
+        Exchange::Coinex => Box::new(coinex::Market::new(account, client)),
+        Exchange::Kucoin => Box::new(kucoin::Market::new(account, client)),
+        Exchange::Binance => Box::new(binance::Market::new(account, client)),
+        Exchange::Okx => Box::new(okx::Market::new(account, client)),
+    })
 }
 
+/// Order IDs come as numbers or strings, read both as a string.
+pub fn id_field(value: &Value, name: &str) -> ExchangeResult<String> {
+    match value.get(name) {
+        Some(Value::String(s)) => Ok(s.to_string()),
+        Some(Value::Number(n)) => Ok(n.to_string()),
+        _ => Err(ExchangeError::decode(format!("{} not found", name))),
+    }
+}
 
+/// Most exchanges return numbers as strings, accept both.
+/// Numbers are read from their text so the decimal value is exact.
+pub fn number_field(value: &Value, name: &str) -> ExchangeResult<Decimal> {
+    let field = value.get(name).ok_or_else(|| ExchangeError::decode(format!("{} not found", name)))?;
+    match field {
+        Value::Number(n) => parse_decimal(&n.to_string()),
+        Value::String(s) => parse_decimal(s),
+        _ => None,
+    }.ok_or_else(|| ExchangeError::decode(format!("{} is not a number", name)))
+}
 
+/// Round `value` to a multiple of `step`.
+pub fn round_to_step(value: Decimal, step: Decimal, round: fn(&Decimal) -> Decimal) -> Decimal {
+    if step <= Decimal::ZERO {
+        return value;
+    }
+    (round(&(value / step)) * step).normalize()
+}
+
+fn parse_decimal(s: &str) -> Option<Decimal> {
+    Decimal::from_str(s).or_else(|_| Decimal::from_scientific(s)).ok()
+}
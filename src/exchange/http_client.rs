@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use reqwest::Client;
+
+use crate::common::environments::{http_connect_timeout_secs, http_keep_alive_secs, http_pool_idle_timeout_secs, http_pool_max_idle_per_host, http_timeout_secs};
+
+/// Build the connection-pooled client shared by every exchange connector.
+/// Cloning the returned client is cheap and reuses the same pool.
+pub fn build() -> reqwest::Result<Client> {
+    Client::builder()
+        .use_rustls_tls()
+        .timeout(Duration::from_secs(http_timeout_secs()))
+        .connect_timeout(Duration::from_secs(http_connect_timeout_secs()))
+        .tcp_keepalive(Duration::from_secs(http_keep_alive_secs()))
+        .pool_idle_timeout(Duration::from_secs(http_pool_idle_timeout_secs()))
+        .pool_max_idle_per_host(http_pool_max_idle_per_host())
+        .build()
+}
@@ -0,0 +1,253 @@
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{id_field, number_field};
+use crate::exchange::structs::{Order, OrderAck, OrderSide, OrderType, PositionInformation, TimeInForce, TriggerBy};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderRequest {
+    #[serde(rename = "clientOid")]
+    pub _client_oid: String,
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "side")]
+    pub _side: String,
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(rename = "leverage")]
+    pub _leverage: String,
+    #[serde(rename = "price", skip_serializing_if = "Option::is_none")]
+    pub _price: Option<String>,
+    #[serde(rename = "size")]
+    pub _size: i64,
+    #[serde(rename = "timeInForce", skip_serializing_if = "Option::is_none")]
+    pub _time_in_force: Option<String>,
+    #[serde(rename = "postOnly")]
+    pub _post_only: bool,
+    #[serde(rename = "reduceOnly")]
+    pub _reduce_only: bool,
+    #[serde(rename = "stop", skip_serializing_if = "Option::is_none")]
+    pub _stop: Option<String>,
+    #[serde(rename = "stopPriceType", skip_serializing_if = "Option::is_none")]
+    pub _stop_price_type: Option<String>,
+    #[serde(rename = "stopPrice", skip_serializing_if = "Option::is_none")]
+    pub _stop_price: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PositionRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelAllRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccountOverviewRequest {
+    #[serde(rename = "currency")]
+    pub _currency: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarginModeRequest {
+    #[serde(rename = "symbol")]
+    pub _symbol: String,
+    #[serde(rename = "marginMode")]
+    pub _margin_mode: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApiResponse {
+    pub code: String,
+    #[serde(default)]
+    pub data: Value,
+    #[serde(default)]
+    pub msg: String,
+}
+
+/// Contract specification, sizes on KuCoin are sent in lots of `multiplier` coins.
+pub struct Contract {
+    pub multiplier: Decimal,
+    pub lot_size: Decimal,
+}
+
+impl OrderAck {
+    pub fn from_kucoin(value: &Value, client_oid: &str) -> ExchangeResult<OrderAck> {
+        Ok(OrderAck::accepted(&id_field(value, "orderId")?, client_oid))
+    }
+}
+
+impl ApiResponse {
+    pub fn into_result(self) -> ExchangeResult<Value> {
+        let code = self.code.parse::<i64>().unwrap_or(-1);
+        let message = self.msg;
+        match code {
+            200000 => Ok(self.data),
+            400001..=400005 | 411100 => Err(ExchangeError::Auth { code, message }),
+            429000 => Err(ExchangeError::RateLimited { code, message }),
+            300003 => Err(ExchangeError::InsufficientBalance { code, message }),
+            _ => Err(ExchangeError::Exchange { code, message }),
+        }
+    }
+}
+
+//------------- Order Request -------------
+impl OrderRequest {
+    pub fn new(client_oid: String, order: &Order, leverage: i32, size: i64) -> Self {
+        let post_only = matches!((&order.order_type, &order.time_in_force), (OrderType::Limit, TimeInForce::PostOnly));
+        let (price, time_in_force) = match order.order_type {
+            OrderType::Limit => (Some(order.price.to_string()), Some(get_time_in_force(&order.time_in_force))),
+            OrderType::Market => (None, None),
+        };
+        OrderRequest {
+            _client_oid: client_oid,
+            _symbol: order.symbol.to_string(),
+            _side: get_side(&order.side),
+            _type: order.order_type.to_string().to_lowercase(),
+            _leverage: leverage.to_string(),
+            _price: price,
+            _size: size,
+            _time_in_force: time_in_force,
+            _post_only: post_only,
+            _reduce_only: order.reduce_only.unwrap_or(false),
+            _stop: None,
+            _stop_price_type: None,
+            _stop_price: None,
+        }
+    }
+
+    /// Turn the order into a stop order, `up` triggers when the `trigger_by` price rises above `stop_price`.
+    pub fn with_stop(mut self, up: bool, stop_price: Decimal, trigger_by: TriggerBy) -> Self {
+        self._stop = Some(String::from(if up { "up" } else { "down" }));
+        self._stop_price_type = Some(String::from(match trigger_by {
+            TriggerBy::Last => "TP",
+            TriggerBy::Mark => "MP",
+            TriggerBy::Index => "IP",
+        }));
+        self._stop_price = Some(stop_price.to_string());
+        self
+    }
+
+    pub fn to_json(&self) -> ExchangeResult<String> {
+        Ok(serde_json::to_string(&self)?)
+    }
+}
+
+impl PositionRequest {
+    pub fn new(symbol: &str) -> Self {
+        PositionRequest {
+            _symbol: symbol.into(),
+        }
+    }
+
+    pub fn get_url_encoded(&self) -> ExchangeResult<String> {
+        serde_urlencoded::to_string(self).map_err(|e| ExchangeError::decode(e.to_string()))
+    }
+}
+
+impl CancelAllRequest {
+    pub fn new(symbol: &str) -> Self {
+        CancelAllRequest {
+            _symbol: symbol.into(),
+        }
+    }
+
+    pub fn get_url_encoded(&self) -> ExchangeResult<String> {
+        serde_urlencoded::to_string(self).map_err(|e| ExchangeError::decode(e.to_string()))
+    }
+}
+
+impl AccountOverviewRequest {
+    pub fn new(currency: &str) -> Self {
+        AccountOverviewRequest {
+            _currency: currency.into(),
+        }
+    }
+
+    pub fn get_url_encoded(&self) -> ExchangeResult<String> {
+        serde_urlencoded::to_string(self).map_err(|e| ExchangeError::decode(e.to_string()))
+    }
+}
+
+impl MarginModeRequest {
+    pub fn new(symbol: &str, isolated: bool) -> Self {
+        MarginModeRequest {
+            _symbol: symbol.into(),
+            _margin_mode: String::from(if isolated { "ISOLATED" } else { "CROSS" }),
+        }
+    }
+
+    pub fn to_json(&self) -> ExchangeResult<String> {
+        Ok(serde_json::to_string(&self)?)
+    }
+}
+
+impl Contract {
+    pub fn from_value(value: &Value) -> ExchangeResult<Contract> {
+        Ok(Contract {
+            multiplier: number_field(value, "multiplier")?,
+            lot_size: number_field(value, "lotSize")?,
+        })
+    }
+
+    /// Convert a coin quantity into whole lots, rounding down.
+    pub fn to_lots(&self, qty: Decimal) -> i64 {
+        let lots = (qty / self.multiplier / self.lot_size).floor() * self.lot_size;
+        lots.to_i64().unwrap_or_default()
+    }
+
+    pub fn to_qty(&self, lots: Decimal) -> Decimal {
+        lots * self.multiplier
+    }
+}
+
+impl PositionInformation {
+    pub fn from_kucoin(value: &Value, contract: &Contract) -> ExchangeResult<PositionInformation> {
+        let current_qty = number_field(value, "currentQty")?;
+        let side = if current_qty >= Decimal::ZERO { OrderSide::Long } else { OrderSide::Short };
+        let size = contract.to_qty(current_qty.abs());
+        let is_open = value.get("isOpen").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        Ok(PositionInformation {
+            entry_price: if is_open { number_field(value, "avgEntryPrice")? } else { Decimal::ZERO },
+            free_qty: size,
+            is_isolated: !value.get("crossMode").and_then(|v| v.as_bool()).unwrap_or(false),
+            leverage: number_field(value, "realLeverage").unwrap_or_default().to_i32().unwrap_or_default(),
+            liq_price: number_field(value, "liquidationPrice").unwrap_or_default(),
+            side,
+            size,
+            symbol: signal_symbol(value.get("symbol").and_then(|s| s.as_str()).unwrap_or_default()),
+            unrealised_pnl: number_field(value, "unrealisedPnl").unwrap_or_default(),
+        })
+    }
+}
+
+/// Map the KuCoin contract `XBTUSDTM` back to the signal symbol `BTCUSDT`.
+fn signal_symbol(contract: &str) -> String {
+    let symbol = contract.strip_suffix('M').unwrap_or(contract);
+    match symbol.strip_prefix("XBT") {
+        Some(quote) => format!("BTC{}", quote),
+        None => symbol.to_string(),
+    }
+}
+
+fn get_side(side: &OrderSide) -> String {
+    match side {
+        OrderSide::Short => String::from("sell"),
+        OrderSide::Long => String::from("buy"),
+    }
+}
+
+fn get_time_in_force(time_in_force: &TimeInForce) -> String {
+    match time_in_force {
+        TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill => String::from("IOC"),
+        TimeInForce::GoodTillCancel | TimeInForce::PostOnly => String::from("GTC"),
+    }
+}
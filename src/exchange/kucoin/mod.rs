@@ -0,0 +1,271 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use async_trait::async_trait;
+use data_encoding::BASE64;
+use reqwest::Client;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use ring::hmac;
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+use crate::common::utils::get_current_timestamp;
+use crate::exchange::account::{AccountConfig, Credentials};
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{MarketApi, number_field};
+use crate::exchange::kucoin::market_structs::*;
+use crate::exchange::structs::*;
+
+mod market_structs;
+
+pub struct Market {
+    client: Client,
+    credentials: Credentials,
+    base_url: &'static str,
+    leverage: AtomicI32,
+}
+
+const MAIN_BASE_URL: &str = "https://api-futures.kucoin.com";
+const BASE_URL_TESTNET: &str = "https://api-sandbox-futures.kucoin.com";
+
+const ORDER_PATH: &str = "/api/v1/orders";
+const POSITION_PATH: &str = "/api/v1/position";
+const POSITIONS_PATH: &str = "/api/v1/positions";
+const ACCOUNT_OVERVIEW_PATH: &str = "/api/v1/account-overview";
+const CONTRACT_PATH: &str = "/api/v1/contracts";
+const MARGIN_MODE_PATH: &str = "/api/v2/position/changeMarginMode";
+const STOP_ORDERS_PATH: &str = "/api/v1/stopOrders";
+
+impl Market {
+    pub fn new(account: &AccountConfig, client: Client) -> Self {
+        Market {
+            client,
+            credentials: account.credentials.clone(),
+            base_url: if account.testnet { BASE_URL_TESTNET } else { MAIN_BASE_URL },
+            leverage: AtomicI32::new(1),
+        }
+    }
+
+    async fn get(&self, api_path: &str, query: &str) -> ExchangeResult<Value> {
+        let endpoint = if query.is_empty() { api_path.to_string() } else { format!("{}?{}", api_path, query) };
+        call_api(&self.client, self.base_url, &endpoint, HttpMethod::Get, String::new(), &self.credentials).await?.into_result()
+    }
+
+    async fn post(&self, api_path: &str, body: String) -> ExchangeResult<Value> {
+        call_api(&self.client, self.base_url, api_path, HttpMethod::Post, body, &self.credentials).await?.into_result()
+    }
+
+    async fn delete(&self, api_path: &str, query: &str) -> ExchangeResult<Value> {
+        let endpoint = format!("{}?{}", api_path, query);
+        call_api(&self.client, self.base_url, &endpoint, HttpMethod::Delete, String::new(), &self.credentials).await?.into_result()
+    }
+
+    async fn contract(&self, symbol: &str) -> ExchangeResult<Contract> {
+        let result = self.get(&format!("{}/{}", CONTRACT_PATH, symbol), "").await?;
+        Contract::from_value(&result)
+    }
+
+    async fn place(&self, request: OrderRequest) -> ExchangeResult<OrderAck> {
+        if request._size <= 0 {
+            return Err(ExchangeError::Exchange { code: -1, message: format!("Order size for {} is below one lot", request._symbol) });
+        }
+        let result = self.post(ORDER_PATH, request.to_json()?).await?;
+        OrderAck::from_kucoin(&result, &request._client_oid)
+    }
+
+    /// Place a reduce-only stop market order closing `qty` of a position opened on `side`.
+    async fn exit_stop(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, stop_price: Decimal, take_profit: bool, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        let contract = self.contract(symbol).await?;
+        let qty = match qty {
+            Some(qty) => qty,
+            None => self.position(symbol).await?.map(|p| p.size).unwrap_or_default(),
+        };
+        let exit = Order {
+            symbol: symbol.to_string(),
+            time_in_force: TimeInForce::GoodTillCancel,
+            price: Decimal::ZERO,
+            qty,
+            reduce_only: Some(true),
+            close_on_trigger: Some(true),
+            order_type: OrderType::Market,
+            leverage: None,
+            side: side.opposite(),
+            take_profit: None,
+            stop_loss: None,
+            order_link_id: options.order_link_id.map(String::from),
+        };
+        // A long takes profit above and stops out below the entry, a short the other way round.
+        let up = matches!(side, OrderSide::Long) == take_profit;
+        let request = OrderRequest::new(client_oid(&exit), &exit, self.leverage.load(Ordering::SeqCst), contract.to_lots(qty))
+            .with_stop(up, stop_price, options.trigger_by.unwrap_or(TriggerBy::Last));
+        self.place(request).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MarketApi for Market {
+    fn triggers_by(&self, _trigger_by: TriggerBy) -> bool {
+        true
+    }
+
+    async fn order(&self, order: Order) -> ExchangeResult<OrderAck> {
+        let symbol = contract_symbol(&order.symbol);
+        let contract = self.contract(&symbol).await?;
+        let leverage = order.leverage.unwrap_or_else(|| self.leverage.load(Ordering::SeqCst));
+        let order = Order { symbol, ..order };
+        let request = OrderRequest::new(client_oid(&order), &order, leverage, contract.to_lots(order.qty));
+        self.place(request).await
+    }
+
+    async fn take_profit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, _stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        match take_profit {
+            Some(price) => self.exit_stop(&contract_symbol(symbol), qty, side, price, true, options).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn stop_loss(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        let symbol = contract_symbol(symbol);
+        if let Some(price) = stop_loss {
+            self.exit_stop(&symbol, qty, side, price, false, options).await?;
+        }
+        if let Some(price) = take_profit {
+            self.exit_stop(&symbol, qty, side, price, true, ExitOptions { order_link_id: None, ..options }).await?;
+        }
+        Ok(())
+    }
+
+    async fn position(&self, symbol: &str) -> ExchangeResult<Option<PositionInformation>> {
+        let symbol = contract_symbol(symbol);
+        let contract = self.contract(&symbol).await?;
+        let query = PositionRequest::new(&symbol).get_url_encoded()?;
+        let result = self.get(POSITION_PATH, &query).await?;
+        if result.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(PositionInformation::from_kucoin(&result, &contract)?))
+    }
+
+    async fn positions(&self) -> ExchangeResult<Vec<PositionInformation>> {
+        let result = self.get(POSITIONS_PATH, "").await?;
+        let mut positions = Vec::new();
+        for value in result.as_array().map(|list| list.as_slice()).unwrap_or_default() {
+            if value.get("isOpen").and_then(|v| v.as_bool()).unwrap_or(false) {
+                let contract = self.contract(value.get("symbol").and_then(|s| s.as_str()).unwrap_or_default()).await?;
+                positions.push(PositionInformation::from_kucoin(value, &contract)?);
+            }
+        }
+        Ok(positions)
+    }
+
+    async fn is_in_position(&self, symbol: &str) -> ExchangeResult<bool> {
+        Ok(match self.position(symbol).await? {
+            Some(position_info) => position_info.entry_price > Decimal::ZERO,
+            None => false,
+        })
+    }
+
+    async fn wallet_available_balance(&self, coin: &str) -> ExchangeResult<Decimal> {
+        let query = AccountOverviewRequest::new(coin).get_url_encoded()?;
+        let result = self.get(ACCOUNT_OVERVIEW_PATH, &query).await?;
+        number_field(&result, "availableBalance")
+    }
+
+    async fn leverage(&self, _symbol: &str, leverage: i32) -> ExchangeResult<()> {
+        // Isolated leverage is sent with every order on KuCoin.
+        self.leverage.store(leverage, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn switch_isolated(&self, symbol: &str, isolated: bool, leverage: i32) -> ExchangeResult<()> {
+        let request = MarginModeRequest::new(&contract_symbol(symbol), isolated);
+        self.post(MARGIN_MODE_PATH, request.to_json()?).await?;
+        self.leverage.store(leverage, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn cancel_all(&self, symbol: &str) -> ExchangeResult<()> {
+        // Open orders and untriggered stop orders are cancelled separately.
+        let query = CancelAllRequest::new(&contract_symbol(symbol)).get_url_encoded()?;
+        self.delete(ORDER_PATH, &query).await?;
+        self.delete(STOP_ORDERS_PATH, &query).await?;
+        Ok(())
+    }
+
+    async fn qty_step(&self, symbol: &str) -> ExchangeResult<Option<Decimal>> {
+        let contract = self.contract(&contract_symbol(symbol)).await?;
+        Ok(Some(contract.to_qty(contract.lot_size)))
+    }
+}
+
+/// Map a signal symbol such as `BTCUSDT` to the KuCoin contract `XBTUSDTM`.
+fn contract_symbol(symbol: &str) -> String {
+    let symbol = symbol.to_uppercase();
+    if symbol.ends_with("USDTM") || symbol.ends_with("USDM") {
+        return symbol;
+    }
+    let symbol = match symbol.strip_prefix("BTC") {
+        Some(quote) => format!("XBT{}", quote),
+        None => symbol,
+    };
+    format!("{}M", symbol)
+}
+
+/// The order's client ID, or a random one when the caller did not name it.
+fn client_oid(order: &Order) -> String {
+    order.order_link_id.clone().unwrap_or_else(|| format!("{:032x}", rand::random::<u128>()))
+}
+
+async fn call_api(client: &Client, base_url: &str, endpoint: &str, method: HttpMethod, body: String, credentials: &Credentials) -> ExchangeResult<ApiResponse> {
+    let passphrase = credentials.passphrase.as_deref().unwrap_or_default();
+    let timestamp = get_current_timestamp();
+    let method_name = match method {
+        HttpMethod::Get => "GET",
+        HttpMethod::Post => "POST",
+        HttpMethod::Delete => "DELETE",
+        HttpMethod::Put => "PUT",
+    };
+
+    // Create sign key
+    let key = hmac::Key::new(hmac::HMAC_SHA256, credentials.api_secret.as_bytes());
+
+    // Sign timestamp + method + endpoint (with query) + body
+    let string_to_sign = format!("{}{}{}{}", timestamp, method_name, endpoint, body);
+    let query_sign = BASE64.encode(hmac::sign(&key, string_to_sign.as_bytes()).as_ref());
+
+    // Sign passphrase
+    let passphrase_sign = BASE64.encode(hmac::sign(&key, passphrase.as_bytes()).as_ref());
+
+    let auth_error = |e: reqwest::header::InvalidHeaderValue| ExchangeError::Auth { code: -1, message: e.to_string() };
+
+    // Set Headers
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.insert("KC-API-KEY", HeaderValue::from_str(&credentials.api_key).map_err(auth_error)?);
+    headers.insert("KC-API-PASSPHRASE", HeaderValue::from_str(&passphrase_sign).map_err(auth_error)?);
+    headers.insert("KC-API-SIGN", HeaderValue::from_str(&query_sign).map_err(auth_error)?);
+    headers.insert("KC-API-TIMESTAMP", HeaderValue::from(timestamp));
+    headers.insert("KC-API-KEY-VERSION", HeaderValue::from(2));
+
+    // Send request
+    let url = format!("{}{}", base_url, endpoint);
+    let resp = match method {
+        HttpMethod::Get => client.get(url).headers(headers).send(),
+        HttpMethod::Delete => client.delete(url).headers(headers).send(),
+        HttpMethod::Post => client.post(url).headers(headers).body(body).send(),
+        HttpMethod::Put => client.put(url).headers(headers).body(body).send(),
+    }.await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if status.as_u16() == 429 {
+        return Err(ExchangeError::RateLimited { code: 429, message: body });
+    }
+    if !status.is_success() {
+        // KuCoin reports auth and validation failures with a json body on 4xx
+        if let Ok(response) = serde_json::from_str::<ApiResponse>(&body) {
+            return Ok(response);
+        }
+        return Err(ExchangeError::HttpStatus { status: status.as_u16(), body });
+    }
+    Ok(serde_json::from_str::<ApiResponse>(&body)?)
+}
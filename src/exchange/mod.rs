@@ -1,3 +1,10 @@
+pub mod account;
+pub mod binance;
 pub mod bybit;
+pub mod coinex;
+pub mod error;
+pub mod http_client;
+pub mod kucoin;
+pub mod okx;
 pub mod structs;
-pub mod general;
\ No newline at end of file
+pub mod general;
@@ -0,0 +1,341 @@
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{id_field, number_field};
+use crate::exchange::structs::{ExitOptions, Order, OrderAck, OrderSide, OrderType, PositionInformation, TimeInForce, TriggerBy};
+
+pub const TD_MODE_ISOLATED: &str = "isolated";
+pub const TD_MODE_CROSS: &str = "cross";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AttachedAlgoOrder {
+    #[serde(rename = "tpTriggerPx", skip_serializing_if = "Option::is_none")]
+    pub _tp_trigger_px: Option<String>,
+    #[serde(rename = "tpOrdPx", skip_serializing_if = "Option::is_none")]
+    pub _tp_ord_px: Option<String>,
+    #[serde(rename = "tpTriggerPxType", skip_serializing_if = "Option::is_none")]
+    pub _tp_trigger_px_type: Option<String>,
+    #[serde(rename = "slTriggerPx", skip_serializing_if = "Option::is_none")]
+    pub _sl_trigger_px: Option<String>,
+    #[serde(rename = "slOrdPx", skip_serializing_if = "Option::is_none")]
+    pub _sl_ord_px: Option<String>,
+    #[serde(rename = "slTriggerPxType", skip_serializing_if = "Option::is_none")]
+    pub _sl_trigger_px_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderRequest {
+    #[serde(rename = "instId")]
+    pub _inst_id: String,
+    #[serde(rename = "tdMode")]
+    pub _td_mode: String,
+    #[serde(rename = "side")]
+    pub _side: String,
+    #[serde(rename = "ordType")]
+    pub _ord_type: String,
+    #[serde(rename = "sz")]
+    pub _sz: String,
+    #[serde(rename = "px", skip_serializing_if = "Option::is_none")]
+    pub _px: Option<String>,
+    #[serde(rename = "reduceOnly")]
+    pub _reduce_only: bool,
+    #[serde(rename = "attachAlgoOrds", skip_serializing_if = "Vec::is_empty")]
+    pub _attach_algo_ords: Vec<AttachedAlgoOrder>,
+    #[serde(rename = "clOrdId", skip_serializing_if = "Option::is_none")]
+    pub _cl_ord_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AlgoOrderRequest {
+    #[serde(rename = "instId")]
+    pub _inst_id: String,
+    #[serde(rename = "tdMode")]
+    pub _td_mode: String,
+    #[serde(rename = "side")]
+    pub _side: String,
+    #[serde(rename = "ordType")]
+    pub _ord_type: String,
+    #[serde(rename = "sz")]
+    pub _sz: String,
+    #[serde(rename = "reduceOnly")]
+    pub _reduce_only: bool,
+    #[serde(flatten)]
+    pub _trigger: AttachedAlgoOrder,
+    #[serde(rename = "algoClOrdId", skip_serializing_if = "Option::is_none")]
+    pub _algo_cl_ord_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetLeverageRequest {
+    #[serde(rename = "instId")]
+    pub _inst_id: String,
+    #[serde(rename = "lever")]
+    pub _lever: String,
+    #[serde(rename = "mgnMode")]
+    pub _mgn_mode: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InstrumentRequest {
+    #[serde(rename = "instType")]
+    pub _inst_type: String,
+    #[serde(rename = "instId", skip_serializing_if = "Option::is_none")]
+    pub _inst_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PendingOrdersRequest {
+    #[serde(rename = "instType")]
+    pub _inst_type: String,
+    #[serde(rename = "instId")]
+    pub _inst_id: String,
+    #[serde(rename = "ordType", skip_serializing_if = "Option::is_none")]
+    pub _ord_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelRequest {
+    #[serde(rename = "instId")]
+    pub _inst_id: String,
+    #[serde(rename = "ordId", skip_serializing_if = "Option::is_none")]
+    pub _ord_id: Option<String>,
+    #[serde(rename = "algoId", skip_serializing_if = "Option::is_none")]
+    pub _algo_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BalanceRequest {
+    #[serde(rename = "ccy")]
+    pub _ccy: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApiResponse {
+    pub code: String,
+    #[serde(default)]
+    pub msg: String,
+    #[serde(default)]
+    pub data: Value,
+}
+
+/// Swap instrument, sizes on OKX are sent in contracts of `ct_val` coins.
+pub struct Instrument {
+    pub ct_val: Decimal,
+    pub lot_sz: Decimal,
+}
+
+impl OrderAck {
+    pub fn from_okx(value: &Value) -> ExchangeResult<OrderAck> {
+        let order_link_id = value.get("clOrdId").and_then(|id| id.as_str()).unwrap_or_default();
+        Ok(OrderAck::accepted(&id_field(value, "ordId")?, order_link_id))
+    }
+}
+
+impl ApiResponse {
+    pub fn into_result(self) -> ExchangeResult<Value> {
+        // Order endpoints report the real reason per item in `sCode`/`sMsg`.
+        let item = self.data.get(0);
+        let (code, message) = match item.and_then(|i| i.get("sCode")).and_then(|c| c.as_str()) {
+            Some(s_code) if s_code != "0" => (s_code.to_string(), item.and_then(|i| i.get("sMsg")).and_then(|m| m.as_str()).unwrap_or_default().to_string()),
+            _ => (self.code, self.msg),
+        };
+        let code = code.parse::<i64>().unwrap_or(-1);
+        match code {
+            0 => Ok(self.data),
+            50101..=50105 | 50111..=50114 => Err(ExchangeError::Auth { code, message }),
+            50011 | 50061 => Err(ExchangeError::RateLimited { code, message }),
+            51008 | 51131 => Err(ExchangeError::InsufficientBalance { code, message }),
+            _ => Err(ExchangeError::Exchange { code, message }),
+        }
+    }
+}
+
+//------------- Order Request -------------
+impl OrderRequest {
+    pub fn new(inst_id: &str, td_mode: &str, order: &Order, sz: String) -> Self {
+        let ord_type = match (&order.order_type, &order.time_in_force) {
+            (OrderType::Market, _) => "market",
+            (OrderType::Limit, TimeInForce::PostOnly) => "post_only",
+            (OrderType::Limit, TimeInForce::ImmediateOrCancel) => "ioc",
+            (OrderType::Limit, TimeInForce::FillOrKill) => "fok",
+            (OrderType::Limit, TimeInForce::GoodTillCancel) => "limit",
+        };
+        let attach_algo_ords = if order.take_profit.is_some() || order.stop_loss.is_some() {
+            vec![AttachedAlgoOrder::new(order.take_profit, order.stop_loss, None)]
+        } else {
+            vec![]
+        };
+        OrderRequest {
+            _inst_id: inst_id.to_string(),
+            _td_mode: td_mode.to_string(),
+            _side: get_side(&order.side),
+            _ord_type: ord_type.to_string(),
+            _sz: sz,
+            _px: match order.order_type {
+                OrderType::Limit => Some(order.price.to_string()),
+                OrderType::Market => None,
+            },
+            _reduce_only: order.reduce_only.unwrap_or(false),
+            _attach_algo_ords: attach_algo_ords,
+            _cl_ord_id: order.order_link_id.clone(),
+        }
+    }
+}
+
+impl AttachedAlgoOrder {
+    /// Trigger prices executed at market (`-1`), triggered by the last price unless `trigger_by` says otherwise.
+    pub fn new(take_profit: Option<Decimal>, stop_loss: Option<Decimal>, trigger_by: Option<TriggerBy>) -> Self {
+        let trigger_px_type = trigger_by.map(get_trigger_px_type);
+        AttachedAlgoOrder {
+            _tp_trigger_px: take_profit.map(|p| p.to_string()),
+            _tp_ord_px: take_profit.map(|_| String::from("-1")),
+            _tp_trigger_px_type: take_profit.and(trigger_px_type.clone()),
+            _sl_trigger_px: stop_loss.map(|p| p.to_string()),
+            _sl_ord_px: stop_loss.map(|_| String::from("-1")),
+            _sl_trigger_px_type: stop_loss.and(trigger_px_type),
+        }
+    }
+}
+
+impl AlgoOrderRequest {
+    /// Reduce-only conditional exit for a position opened on `side`.
+    pub fn exit(inst_id: &str, td_mode: &str, side: &OrderSide, sz: String, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions) -> Self {
+        AlgoOrderRequest {
+            _inst_id: inst_id.to_string(),
+            _td_mode: td_mode.to_string(),
+            _side: get_side(&side.opposite()),
+            _ord_type: String::from(if take_profit.is_some() && stop_loss.is_some() { "oco" } else { "conditional" }),
+            _sz: sz,
+            _reduce_only: true,
+            _trigger: AttachedAlgoOrder::new(take_profit, stop_loss, options.trigger_by),
+            _algo_cl_ord_id: options.order_link_id.map(String::from),
+        }
+    }
+}
+
+impl SetLeverageRequest {
+    pub fn new(inst_id: &str, leverage: i32, td_mode: &str) -> Self {
+        SetLeverageRequest {
+            _inst_id: inst_id.to_string(),
+            _lever: leverage.to_string(),
+            _mgn_mode: td_mode.to_string(),
+        }
+    }
+}
+
+impl InstrumentRequest {
+    pub fn swap(inst_id: &str) -> Self {
+        InstrumentRequest {
+            _inst_type: String::from("SWAP"),
+            _inst_id: Some(inst_id.to_string()),
+        }
+    }
+
+    /// Every swap.
+    pub fn swaps() -> Self {
+        InstrumentRequest {
+            _inst_type: String::from("SWAP"),
+            _inst_id: None,
+        }
+    }
+}
+
+impl PendingOrdersRequest {
+    /// Open orders on `inst_id`.
+    pub fn orders(inst_id: &str) -> Self {
+        PendingOrdersRequest {
+            _inst_type: String::from("SWAP"),
+            _inst_id: inst_id.to_string(),
+            _ord_type: None,
+        }
+    }
+
+    /// Conditional and OCO exits on `inst_id`.
+    pub fn algos(inst_id: &str) -> Self {
+        PendingOrdersRequest {
+            _inst_type: String::from("SWAP"),
+            _inst_id: inst_id.to_string(),
+            _ord_type: Some(String::from("conditional,oco")),
+        }
+    }
+}
+
+impl CancelRequest {
+    pub fn order(inst_id: &str, ord_id: String) -> Self {
+        CancelRequest { _inst_id: inst_id.to_string(), _ord_id: Some(ord_id), _algo_id: None }
+    }
+
+    pub fn algo(inst_id: &str, algo_id: String) -> Self {
+        CancelRequest { _inst_id: inst_id.to_string(), _ord_id: None, _algo_id: Some(algo_id) }
+    }
+}
+
+impl BalanceRequest {
+    pub fn new(ccy: &str) -> Self {
+        BalanceRequest { _ccy: ccy.to_string() }
+    }
+}
+
+impl Instrument {
+    pub fn from_value(value: &Value) -> ExchangeResult<Instrument> {
+        Ok(Instrument {
+            ct_val: number_field(value, "ctVal")?,
+            lot_sz: number_field(value, "lotSz")?,
+        })
+    }
+
+    /// Convert a coin quantity into contracts, rounded down to the lot size.
+    pub fn to_contracts(&self, qty: Decimal) -> Decimal {
+        (qty / self.ct_val / self.lot_sz).floor() * self.lot_sz
+    }
+
+    pub fn to_qty(&self, contracts: Decimal) -> Decimal {
+        contracts * self.ct_val
+    }
+}
+
+impl PositionInformation {
+    pub fn from_okx(value: &Value, instrument: &Instrument) -> ExchangeResult<PositionInformation> {
+        let pos = number_field(value, "pos")?;
+        let side = match value.get("posSide").and_then(|s| s.as_str()) {
+            Some("long") => OrderSide::Long,
+            Some("short") => OrderSide::Short,
+            _ => if pos >= Decimal::ZERO { OrderSide::Long } else { OrderSide::Short },
+        };
+
+        Ok(PositionInformation {
+            entry_price: number_field(value, "avgPx").unwrap_or_default(),
+            free_qty: instrument.to_qty(number_field(value, "availPos").unwrap_or_default().abs()),
+            is_isolated: value.get("mgnMode").and_then(|m| m.as_str()) == Some(TD_MODE_ISOLATED),
+            leverage: number_field(value, "lever").unwrap_or_default().to_i32().unwrap_or_default(),
+            liq_price: number_field(value, "liqPx").unwrap_or_default(),
+            side,
+            size: instrument.to_qty(pos.abs()),
+            symbol: signal_symbol(value.get("instId").and_then(|s| s.as_str()).unwrap_or_default()),
+            unrealised_pnl: number_field(value, "upl").unwrap_or_default(),
+        })
+    }
+}
+
+/// Map the OKX swap `BTC-USDT-SWAP` back to the signal symbol `BTCUSDT`.
+fn signal_symbol(inst_id: &str) -> String {
+    inst_id.trim_end_matches("-SWAP").replace('-', "")
+}
+
+fn get_side(side: &OrderSide) -> String {
+    match side {
+        OrderSide::Short => String::from("sell"),
+        OrderSide::Long => String::from("buy"),
+    }
+}
+
+fn get_trigger_px_type(trigger_by: TriggerBy) -> String {
+    match trigger_by {
+        TriggerBy::Last => String::from("last"),
+        TriggerBy::Mark => String::from("mark"),
+        TriggerBy::Index => String::from("index"),
+    }
+}
@@ -0,0 +1,274 @@
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use data_encoding::BASE64;
+use reqwest::Client;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use ring::hmac;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::exchange::account::{AccountConfig, Credentials};
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::{MarketApi, number_field};
+use crate::exchange::okx::market_structs::*;
+use crate::exchange::structs::*;
+
+mod market_structs;
+
+pub struct Market {
+    client: Client,
+    credentials: Credentials,
+    simulated: bool,
+    td_mode: RwLock<&'static str>,
+}
+
+const BASE_URL: &str = "https://www.okx.com";
+
+const ORDER_PATH: &str = "/api/v5/trade/order";
+const ALGO_ORDER_PATH: &str = "/api/v5/trade/order-algo";
+const SET_LEVERAGE_PATH: &str = "/api/v5/account/set-leverage";
+const POSITIONS_PATH: &str = "/api/v5/account/positions";
+const BALANCE_PATH: &str = "/api/v5/account/balance";
+const INSTRUMENTS_PATH: &str = "/api/v5/public/instruments";
+const PENDING_ORDERS_PATH: &str = "/api/v5/trade/orders-pending";
+const PENDING_ALGOS_PATH: &str = "/api/v5/trade/orders-algo-pending";
+const CANCEL_ORDERS_PATH: &str = "/api/v5/trade/cancel-batch-orders";
+const CANCEL_ALGOS_PATH: &str = "/api/v5/trade/cancel-algos";
+
+/// Most orders and algo orders cancelled by one batch request.
+const CANCEL_ORDERS_BATCH: usize = 20;
+const CANCEL_ALGOS_BATCH: usize = 10;
+
+impl Market {
+    pub fn new(account: &AccountConfig, client: Client) -> Self {
+        Market {
+            client,
+            credentials: account.credentials.clone(),
+            simulated: account.testnet,
+            td_mode: RwLock::new(TD_MODE_ISOLATED),
+        }
+    }
+
+    fn td_mode(&self) -> &'static str {
+        *self.td_mode.read().unwrap()
+    }
+
+    async fn get<T: Serialize>(&self, api_path: &str, request: &T) -> ExchangeResult<Value> {
+        let query = serde_urlencoded::to_string(request).map_err(|e| ExchangeError::decode(e.to_string()))?;
+        let endpoint = format!("{}?{}", api_path, query);
+        call_api(&self.client, &endpoint, HttpMethod::Get, String::new(), &self.credentials, self.simulated).await?.into_result()
+    }
+
+    async fn post<T: Serialize>(&self, api_path: &str, request: &T) -> ExchangeResult<Value> {
+        let body = serde_json::to_string(request)?;
+        call_api(&self.client, api_path, HttpMethod::Post, body, &self.credentials, self.simulated).await?.into_result()
+    }
+
+    async fn instrument(&self, inst_id: &str) -> ExchangeResult<Instrument> {
+        let result = self.get(INSTRUMENTS_PATH, &InstrumentRequest::swap(inst_id)).await?;
+        let value = result.get(0).ok_or_else(|| ExchangeError::decode(format!("Instrument {} not found", inst_id)))?;
+        Instrument::from_value(value)
+    }
+
+    async fn contracts(&self, inst_id: &str, qty: Decimal) -> ExchangeResult<String> {
+        let contracts = self.instrument(inst_id).await?.to_contracts(qty);
+        if contracts <= Decimal::ZERO {
+            return Err(ExchangeError::Exchange { code: -1, message: format!("Order size for {} is below one contract", inst_id) });
+        }
+        Ok(contracts.to_string())
+    }
+
+    async fn exit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        if take_profit.is_none() && stop_loss.is_none() {
+            return Ok(());
+        }
+        let inst_id = inst_id(symbol);
+        let qty = match qty {
+            Some(qty) => qty,
+            None => self.position(symbol).await?.map(|p| p.size).unwrap_or_default(),
+        };
+        let sz = self.contracts(&inst_id, qty).await?;
+        let request = AlgoOrderRequest::exit(&inst_id, self.td_mode(), side, sz, take_profit, stop_loss, options);
+        self.post(ALGO_ORDER_PATH, &request).await?;
+        Ok(())
+    }
+
+    /// IDs under `id_field` of the pending orders listed by `request`.
+    async fn pending_ids(&self, api_path: &str, request: &PendingOrdersRequest, id_field: &str) -> ExchangeResult<Vec<String>> {
+        let result = self.get(api_path, request).await?;
+        Ok(result.as_array().map(|list| list.as_slice()).unwrap_or_default()
+            .iter()
+            .filter_map(|order| order.get(id_field).and_then(|id| id.as_str()).map(String::from))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl MarketApi for Market {
+    fn triggers_by(&self, _trigger_by: TriggerBy) -> bool {
+        true
+    }
+
+    async fn order(&self, order: Order) -> ExchangeResult<OrderAck> {
+        let inst_id = inst_id(&order.symbol);
+        let sz = self.contracts(&inst_id, order.qty).await?;
+        let request = OrderRequest::new(&inst_id, self.td_mode(), &order, sz);
+        let result = self.post(ORDER_PATH, &request).await?;
+        let ack = result.get(0).ok_or_else(|| ExchangeError::decode("Order acknowledgement is empty"))?;
+        OrderAck::from_okx(ack)
+    }
+
+    async fn take_profit(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, _stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        self.exit(symbol, qty, side, take_profit, None, options).await
+    }
+
+    async fn stop_loss(&self, symbol: &str, qty: Option<Decimal>, side: &OrderSide, take_profit: Option<Decimal>, stop_loss: Option<Decimal>, options: ExitOptions<'_>) -> ExchangeResult<()> {
+        self.exit(symbol, qty, side, take_profit, stop_loss, options).await
+    }
+
+    async fn position(&self, symbol: &str) -> ExchangeResult<Option<PositionInformation>> {
+        let inst_id = inst_id(symbol);
+        let result = self.get(POSITIONS_PATH, &InstrumentRequest::swap(&inst_id)).await?;
+        let value = result.as_array()
+            .and_then(|positions| positions.iter().find(|p| number_field(p, "pos").map(|pos| pos != Decimal::ZERO).unwrap_or(false)));
+        match value {
+            Some(value) => {
+                let instrument = self.instrument(&inst_id).await?;
+                Ok(Some(PositionInformation::from_okx(value, &instrument)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn positions(&self) -> ExchangeResult<Vec<PositionInformation>> {
+        let result = self.get(POSITIONS_PATH, &InstrumentRequest::swaps()).await?;
+        let mut positions = Vec::new();
+        for value in result.as_array().map(|list| list.as_slice()).unwrap_or_default() {
+            if number_field(value, "pos").map(|pos| pos != Decimal::ZERO).unwrap_or(false) {
+                let inst_id = value.get("instId").and_then(|s| s.as_str()).unwrap_or_default();
+                let instrument = self.instrument(inst_id).await?;
+                positions.push(PositionInformation::from_okx(value, &instrument)?);
+            }
+        }
+        Ok(positions)
+    }
+
+    async fn is_in_position(&self, symbol: &str) -> ExchangeResult<bool> {
+        Ok(match self.position(symbol).await? {
+            Some(position_info) => position_info.entry_price > Decimal::ZERO,
+            None => false,
+        })
+    }
+
+    async fn wallet_available_balance(&self, coin: &str) -> ExchangeResult<Decimal> {
+        let result = self.get(BALANCE_PATH, &BalanceRequest::new(coin)).await?;
+        let detail = result.get(0)
+            .and_then(|account| account.get("details"))
+            .and_then(|details| details.as_array())
+            .and_then(|details| details.iter().find(|d| d.get("ccy").and_then(|c| c.as_str()) == Some(coin)));
+        match detail {
+            Some(detail) => number_field(detail, "availBal"),
+            None => Ok(Decimal::ZERO),
+        }
+    }
+
+    async fn leverage(&self, symbol: &str, leverage: i32) -> ExchangeResult<()> {
+        let request = SetLeverageRequest::new(&inst_id(symbol), leverage, self.td_mode());
+        self.post(SET_LEVERAGE_PATH, &request).await?;
+        Ok(())
+    }
+
+    async fn switch_isolated(&self, symbol: &str, isolated: bool, leverage: i32) -> ExchangeResult<()> {
+        // OKX picks the margin mode per order, remember it and set the leverage for that mode.
+        *self.td_mode.write().unwrap() = if isolated { TD_MODE_ISOLATED } else { TD_MODE_CROSS };
+        self.leverage(symbol, leverage).await
+    }
+
+    async fn cancel_all(&self, symbol: &str) -> ExchangeResult<()> {
+        // OKX has no cancel-all, the pending orders and exits are listed and cancelled in batches.
+        let inst_id = inst_id(symbol);
+        let orders = self.pending_ids(PENDING_ORDERS_PATH, &PendingOrdersRequest::orders(&inst_id), "ordId").await?;
+        for batch in orders.chunks(CANCEL_ORDERS_BATCH) {
+            let request: Vec<CancelRequest> = batch.iter().map(|id| CancelRequest::order(&inst_id, id.clone())).collect();
+            self.post(CANCEL_ORDERS_PATH, &request).await?;
+        }
+        let algos = self.pending_ids(PENDING_ALGOS_PATH, &PendingOrdersRequest::algos(&inst_id), "algoId").await?;
+        for batch in algos.chunks(CANCEL_ALGOS_BATCH) {
+            let request: Vec<CancelRequest> = batch.iter().map(|id| CancelRequest::algo(&inst_id, id.clone())).collect();
+            self.post(CANCEL_ALGOS_PATH, &request).await?;
+        }
+        Ok(())
+    }
+
+    async fn qty_step(&self, symbol: &str) -> ExchangeResult<Option<Decimal>> {
+        let instrument = self.instrument(&inst_id(symbol)).await?;
+        Ok(Some(instrument.to_qty(instrument.lot_sz)))
+    }
+}
+
+/// Map a signal symbol such as `BTCUSDT` to the OKX swap `BTC-USDT-SWAP`.
+fn inst_id(symbol: &str) -> String {
+    let symbol = symbol.to_uppercase();
+    if symbol.contains('-') {
+        return symbol;
+    }
+    for quote in ["USDT", "USDC", "USD"].iter() {
+        if let Some(base) = symbol.strip_suffix(quote) {
+            return format!("{}-{}-SWAP", base, quote);
+        }
+    }
+    symbol
+}
+
+async fn call_api(client: &Client, endpoint: &str, method: HttpMethod, body: String, credentials: &Credentials, simulated: bool) -> ExchangeResult<ApiResponse> {
+    let timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+    let method_name = match method {
+        HttpMethod::Get => "GET",
+        HttpMethod::Post => "POST",
+        HttpMethod::Delete => "DELETE",
+        HttpMethod::Put => "PUT",
+    };
+
+    // Sign timestamp + method + request path (with query) + body
+    let key = hmac::Key::new(hmac::HMAC_SHA256, credentials.api_secret.as_bytes());
+    let string_to_sign = format!("{}{}{}{}", timestamp, method_name, endpoint, body);
+    let sign = BASE64.encode(hmac::sign(&key, string_to_sign.as_bytes()).as_ref());
+
+    let auth_error = |e: reqwest::header::InvalidHeaderValue| ExchangeError::Auth { code: -1, message: e.to_string() };
+
+    // Set Headers
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.insert("OK-ACCESS-KEY", HeaderValue::from_str(&credentials.api_key).map_err(auth_error)?);
+    headers.insert("OK-ACCESS-SIGN", HeaderValue::from_str(&sign).map_err(auth_error)?);
+    headers.insert("OK-ACCESS-TIMESTAMP", HeaderValue::from_str(&timestamp).map_err(auth_error)?);
+    headers.insert("OK-ACCESS-PASSPHRASE", HeaderValue::from_str(credentials.passphrase.as_deref().unwrap_or_default()).map_err(auth_error)?);
+    if simulated {
+        headers.insert("x-simulated-trading", HeaderValue::from_static("1"));
+    }
+
+    // Send request
+    let url = format!("{}{}", BASE_URL, endpoint);
+    let resp = match method {
+        HttpMethod::Get => client.get(url).headers(headers).send(),
+        HttpMethod::Delete => client.delete(url).headers(headers).send(),
+        HttpMethod::Post => client.post(url).headers(headers).body(body).send(),
+        HttpMethod::Put => client.put(url).headers(headers).body(body).send(),
+    }.await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if status.as_u16() == 429 {
+        return Err(ExchangeError::RateLimited { code: 429, message: body });
+    }
+    if !status.is_success() {
+        // OKX reports auth and validation failures with a json body on 4xx
+        if let Ok(response) = serde_json::from_str::<ApiResponse>(&body) {
+            return Ok(response);
+        }
+        return Err(ExchangeError::HttpStatus { status: status.as_u16(), body });
+    }
+    Ok(serde_json::from_str::<ApiResponse>(&body)?)
+}
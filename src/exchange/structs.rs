@@ -2,26 +2,226 @@
 
 use std::fmt::Formatter;
 
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+
 pub enum HttpMethod {
-    POST,
-    GET,
-    DELETE,
-    PUT,
+    Post,
+    Get,
+    Delete,
+    Put,
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
 pub enum OrderSide {
     Short,
     Long,
 }
 
+impl OrderSide {
+    pub fn opposite(&self) -> OrderSide {
+        match self {
+            OrderSide::Long => OrderSide::Short,
+            OrderSide::Short => OrderSide::Long,
+        }
+    }
+
+    /// Check the exits of a position opened on this side at `entry`: a long takes profit
+    /// above and stops out below the entry, a short the other way round.
+    pub fn check_exits(&self, entry: Decimal, take_profit: Option<Decimal>, stop_loss: Option<Decimal>) -> ExchangeResult<()> {
+        let (position, tp_above) = match self {
+            OrderSide::Long => ("long", true),
+            OrderSide::Short => ("short", false),
+        };
+        let legs = [("take profit", take_profit, tp_above), ("stop loss", stop_loss, !tp_above)];
+        for (leg, price, above) in legs.iter() {
+            if let Some(price) = price {
+                let valid = *price > Decimal::ZERO && if *above { *price > entry } else { *price < entry };
+                if !valid {
+                    let direction = if *above { "above" } else { "below" };
+                    return Err(ExchangeError::InvalidOrder(format!("{} {} of a {} must be {} the entry {}", leg, price, position, direction, entry)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Product a signal trades, picks the client it is routed to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MarketType {
+    Spot,
+    SpotMargin,
+    Linear,
+    Inverse,
+}
+
+impl MarketType {
+    pub fn is_spot(&self) -> bool {
+        matches!(self, MarketType::Spot | MarketType::SpotMargin)
+    }
+}
+
+impl std::fmt::Display for MarketType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarketType::Spot => write!(f, "SPOT"),
+            MarketType::SpotMargin => write!(f, "SPOT_MARGIN"),
+            MarketType::Linear => write!(f, "LINEAR"),
+            MarketType::Inverse => write!(f, "INVERSE"),
+        }
+    }
+}
+
+impl std::str::FromStr for MarketType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "SPOT" => Ok(MarketType::Spot),
+            "SPOT_MARGIN" | "MARGIN" => Ok(MarketType::SpotMargin),
+            "LINEAR" => Ok(MarketType::Linear),
+            "INVERSE" => Ok(MarketType::Inverse),
+            _ => Err(format!("Unsupported market type: {}", s)),
+        }
+    }
+}
+
+/// How a contract is margined and sized.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ContractType {
+    /// Spot without borrowing, quantity in coins and no leverage.
+    Spot,
+    /// Spot with borrowing, quantity in coins.
+    SpotMargin,
+    /// USDT margined, quantity in coins (BTCUSDT).
+    Linear,
+    /// Coin margined perpetual, quantity in USD contracts (BTCUSD).
+    InversePerpetual,
+    /// Coin margined contract with an expiry, quantity in USD contracts (BTCUSDZ22).
+    InverseFutures,
+}
+
+impl ContractType {
+    /// Derivative contract type of a symbol.
+    pub fn from_symbol(symbol: &str) -> ContractType {
+        let symbol = symbol.to_uppercase();
+        if symbol.ends_with("USDT") {
+            ContractType::Linear
+        } else if symbol.ends_with("USD") {
+            ContractType::InversePerpetual
+        } else if is_inverse_futures(&symbol) {
+            ContractType::InverseFutures
+        } else {
+            ContractType::Linear
+        }
+    }
+
+    pub fn is_inverse(&self) -> bool {
+        matches!(self, ContractType::InversePerpetual | ContractType::InverseFutures)
+    }
+
+    pub fn is_spot(&self) -> bool {
+        matches!(self, ContractType::Spot | ContractType::SpotMargin)
+    }
+
+    /// Coin the position is margined in: USDT for spot and linear, the base coin for inverse contracts.
+    pub fn margin_coin(&self, symbol: &str) -> String {
+        if self.is_inverse() {
+            let symbol = symbol.to_uppercase();
+            let base_len = symbol.find("USD").unwrap_or(symbol.len());
+            symbol[..base_len].to_string()
+        } else {
+            String::from("USDT")
+        }
+    }
+
+    /// Value of a margin coin balance in USD.
+    pub fn balance_value(&self, balance: Decimal, price: Decimal) -> Decimal {
+        if self.is_inverse() { balance * price } else { balance }
+    }
+
+    /// Order quantity for a value in USD: coins for spot and linear, whole USD contracts for inverse.
+    pub fn notional_qty(&self, notional: Decimal, price: Decimal) -> Decimal {
+        if self.is_inverse() {
+            notional.floor()
+        } else {
+            // Rounded down so the order never exceeds the value it was sized for.
+            (notional / price).round_dp_with_strategy(4, RoundingStrategy::ToZero)
+        }
+    }
+
+    /// Value in USD of an order quantity.
+    pub fn notional(&self, qty: Decimal, price: Decimal) -> Decimal {
+        if self.is_inverse() { qty } else { qty * price }
+    }
+}
+
+/// Inverse futures carry a month code and year after USD, e.g. BTCUSDZ22.
+fn is_inverse_futures(symbol: &str) -> bool {
+    match symbol.find("USD") {
+        Some(i) => {
+            let expiry = &symbol[i + 3..];
+            expiry.len() == 3
+                && expiry.chars().next().map(|c| c.is_ascii_uppercase()).unwrap_or(false)
+                && expiry.chars().skip(1).all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+#[derive(Serialize, Copy, Clone, Debug, PartialEq)]
 pub enum OrderType {
     Market,
     Limit,
 }
 
+/// Price a conditional exit is triggered by.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TriggerBy {
+    Last,
+    Mark,
+    Index,
+}
+
+impl std::str::FromStr for TriggerBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "LAST" | "LASTPRICE" => Ok(TriggerBy::Last),
+            "MARK" | "MARKPRICE" => Ok(TriggerBy::Mark),
+            "INDEX" | "INDEXPRICE" => Ok(TriggerBy::Index),
+            _ => Err(format!("Unsupported trigger price: {}", s)),
+        }
+    }
+}
+
+/// How an exit is placed: its client order ID and the price it is triggered by, the exchange's
+/// default when `None`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ExitOptions<'a> {
+    pub order_link_id: Option<&'a str>,
+    pub trigger_by: Option<TriggerBy>,
+}
+
+/// Profit of a position closed on `symbol` at `closed_at` (ms since the epoch), in the margin coin.
+#[derive(Clone, Debug)]
+pub struct ClosedPnl {
+    pub symbol: String,
+    pub pnl: Decimal,
+    pub closed_at: i64,
+}
+
+/// Stop following the price at `distance`, armed once the price reaches `active_price`.
+#[derive(Clone, Debug)]
+pub struct TrailingStop {
+    pub distance: Decimal,
+    pub active_price: Option<Decimal>,
+}
+
 pub enum TimeInForce {
     GoodTillCancel,
     ImmediateOrCancel,
@@ -32,25 +232,108 @@ pub enum TimeInForce {
 pub struct Order {
     pub symbol: String,
     pub time_in_force: TimeInForce,
-    pub price: f64,
-    pub qty: f64,
+    pub price: Decimal,
+    pub qty: Decimal,
     pub reduce_only: Option<bool>,
     pub close_on_trigger: Option<bool>,
     pub order_type: OrderType,
     pub leverage: Option<i32>,
     pub side: OrderSide,
-    pub take_profit: Option<f64>,
-    pub stop_loss: Option<f64>,
+    pub take_profit: Option<Decimal>,
+    pub stop_loss: Option<Decimal>,
+    /// Client order ID, lets the order be looked up and retried without knowing the exchange ID.
+    pub order_link_id: Option<String>,
+}
+
+#[derive(Serialize, Copy, Clone, Debug, PartialEq)]
+pub enum OrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+    /// Conditional order waiting for its trigger price.
+    Untriggered,
+    /// Conditional order that fired and became a regular order.
+    Triggered,
+}
+
+impl OrderStatus {
+    /// Whether the order can still fill, be amended or be cancelled.
+    pub fn is_open(&self) -> bool {
+        matches!(self, OrderStatus::New | OrderStatus::PartiallyFilled | OrderStatus::Untriggered)
+    }
+}
+
+/// An order as reported by the exchange.
+#[derive(Serialize, Clone, Debug)]
+pub struct OrderInfo {
+    pub order_id: String,
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub status: OrderStatus,
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub executed_qty: Decimal,
+    pub avg_price: Decimal,
+    pub trigger_price: Option<Decimal>,
+    pub reduce_only: bool,
+}
+
+/// What the exchange reported back for a newly placed order.
+#[derive(Serialize, Clone, Debug)]
+pub struct OrderAck {
+    pub order_id: String,
+    pub order_link_id: String,
+    pub status: OrderStatus,
+    pub avg_price: Option<Decimal>,
+    pub executed_qty: Option<Decimal>,
+}
+
+impl OrderAck {
+    /// Exchanges that only return the IDs of an accepted order.
+    pub fn accepted(order_id: &str, order_link_id: &str) -> Self {
+        OrderAck {
+            order_id: order_id.to_string(),
+            order_link_id: order_link_id.to_string(),
+            status: OrderStatus::New,
+            avg_price: None,
+            executed_qty: None,
+        }
+    }
+}
+
+impl From<OrderInfo> for OrderAck {
+    fn from(order: OrderInfo) -> Self {
+        OrderAck {
+            order_id: order.order_id,
+            order_link_id: order.order_link_id,
+            status: order.status,
+            avg_price: if order.avg_price > Decimal::ZERO { Some(order.avg_price) } else { None },
+            executed_qty: Some(order.executed_qty),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PositionInformation {
-    pub entry_price: f64,
-    pub free_qty: f64,
+    pub entry_price: Decimal,
+    pub free_qty: Decimal,
     pub is_isolated: bool,
     pub leverage: i32,
-    pub liq_price: f64,
+    pub liq_price: Decimal,
     pub side: OrderSide,
-    pub size: f64,
+    pub size: Decimal,
     pub symbol: String,
+    /// Profit of the open position at the mark price, in the margin coin.
+    pub unrealised_pnl: Decimal,
+}
+
+impl PositionInformation {
+    /// Value of the position in USD at its entry price.
+    pub fn notional(&self) -> Decimal {
+        ContractType::from_symbol(&self.symbol).notional(self.size, self.entry_price)
+    }
 }
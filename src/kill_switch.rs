@@ -0,0 +1,216 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use log::{error, info, warn};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use sha256::digest;
+
+use crate::common::environments::{daily_loss_limit, kill_switch_flatten, kill_switch_interval_secs, max_consecutive_losses};
+use crate::common::utils::get_current_timestamp;
+use crate::exchange::account::AccountConfig;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general;
+use crate::exchange::general::MarketApi;
+use crate::exchange::structs::{ContractType, MarketType, PositionInformation};
+use crate::robot;
+
+const DAY_MS: i64 = 86_400_000;
+
+/// Kill switch of one account for the current UTC day.
+#[derive(Default)]
+struct DayState {
+    day: i64,
+    /// Symbols entered today, their closed positions count towards the day's PnL.
+    symbols: HashSet<String>,
+    /// Why entries are stopped.
+    tripped: Option<String>,
+    /// When entries were last allowed again, only closes after it count.
+    reset_at: Option<i64>,
+    /// Unrealised PnL of the positions open at the reset, by the market they are held on.
+    unrealised_at_reset: HashMap<MarketType, Decimal>,
+}
+
+/// An account that sent entries and the markets it sent them for.
+struct Watched {
+    account: AccountConfig,
+    market_types: Vec<MarketType>,
+}
+
+static STATES: OnceLock<Mutex<HashMap<String, DayState>>> = OnceLock::new();
+static WATCHED: OnceLock<Mutex<HashMap<String, Watched>>> = OnceLock::new();
+
+fn today() -> i64 {
+    get_current_timestamp() / DAY_MS
+}
+
+/// Run `f` on the state of `account`, which starts afresh on each UTC day.
+fn with_state<T>(account: &str, f: impl FnOnce(&mut DayState) -> T) -> T {
+    let mut states = STATES.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap();
+    let state = states.entry(account.to_string()).or_default();
+    let day = today();
+    if state.day != day {
+        *state = DayState { day, ..DayState::default() };
+    }
+    f(state)
+}
+
+/// Key of an account in logs and state, its exchange and a hash of its API key.
+pub fn account_key(account: &AccountConfig) -> String {
+    format!("{}:{}", account.exchange, &digest(account.credentials.api_key.as_str())[..16])
+}
+
+/// Why entries are stopped for `account` today, `None` while they are allowed.
+pub fn tripped(account: &str) -> Option<String> {
+    with_state(account, |state| state.tripped.clone())
+}
+
+/// Check the kill switch of `account` on `market_type` in `watch_accounts` from now on.
+pub fn watch(account: &AccountConfig, market_type: MarketType) {
+    let mut watched = WATCHED.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap();
+    let watched = watched.entry(account_key(account))
+        .or_insert_with(|| Watched { account: account.clone(), market_types: Vec::new() });
+    if !watched.market_types.contains(&market_type) {
+        watched.market_types.push(market_type);
+    }
+}
+
+/// Markets `account` sent entries for.
+pub fn market_types(account: &str) -> Vec<MarketType> {
+    let watched = WATCHED.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap();
+    watched.get(account).map(|watched| watched.market_types.clone()).unwrap_or_default()
+}
+
+/// Allow entries for `account` again until a limit is hit anew, counting from now. What the positions
+/// open on `markets` have lost by now was counted before, only its change from here on counts.
+pub async fn reset(account: &str, markets: &[(MarketType, Box<dyn MarketApi>)]) -> ExchangeResult<()> {
+    let mut unrealised_at_reset = HashMap::new();
+    if daily_loss_limit().is_some() {
+        for (market_type, market) in markets {
+            unrealised_at_reset.insert(*market_type, unrealised(&usd_positions(market.as_ref()).await?));
+        }
+    }
+    with_state(account, |state| {
+        state.tripped = None;
+        state.reset_at = Some(get_current_timestamp());
+        state.unrealised_at_reset = unrealised_at_reset;
+    });
+    info!("Kill switch reset account:{}", account);
+    Ok(())
+}
+
+/// Check the kill switch of every watched account each `KILL_SWITCH_INTERVAL_SECS`, so that limits
+/// are hit while no entries arrive, and close their positions when `KILL_SWITCH_FLATTEN` is set.
+pub async fn watch_accounts(client: Client) {
+    let mut interval = tokio::time::interval(Duration::from_secs(kill_switch_interval_secs().max(1)));
+    loop {
+        interval.tick().await;
+        let watched: Vec<(String, AccountConfig, Vec<MarketType>)> = {
+            let watched = WATCHED.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap();
+            watched.iter().map(|(key, watched)| (key.clone(), watched.account.clone(), watched.market_types.clone())).collect()
+        };
+        for (key, account, market_types) in watched {
+            for market_type in market_types {
+                if tripped(&key).is_some() {
+                    break;
+                }
+                let market = match general::market(&account, market_type, client.clone()) {
+                    Ok(market) => market,
+                    Err(e) => {
+                        warn!("Kill switch not checked account:{} market:{} {}", &key, market_type, e);
+                        continue;
+                    }
+                };
+                match check(market.as_ref(), market_type, &key, None).await {
+                    Ok(Some(reason)) => {
+                        error!("Kill switch tripped account:{} {}", &key, reason);
+                        if kill_switch_flatten() {
+                            let signal_id = format!("ks{}", get_current_timestamp());
+                            if let Err(e) = robot::flatten(market.as_ref(), &signal_id).await {
+                                error!("Flatten failed account:{} {}", &key, e);
+                            }
+                        }
+                    }
+                    Ok(None) => {}
+                    // Entries on it are refused instead, see `check`.
+                    Err(ExchangeError::Unsupported(_)) => {}
+                    Err(e) => warn!("Kill switch check failed account:{} market:{} {}", &key, market_type, e),
+                }
+            }
+        }
+    }
+}
+
+/// Inverse contracts settle their PnL in the coin, only stablecoin settled PnL adds up in USD.
+fn settled_in_usd(symbol: &str) -> bool {
+    !ContractType::from_symbol(symbol).is_inverse()
+}
+
+/// Open positions on `market` settled in USD.
+async fn usd_positions(market: &dyn MarketApi) -> ExchangeResult<Vec<PositionInformation>> {
+    Ok(market.positions().await?
+        .into_iter()
+        .filter(|position| settled_in_usd(&position.symbol))
+        .collect())
+}
+
+fn unrealised(positions: &[PositionInformation]) -> Decimal {
+    positions.iter().map(|position| position.unrealised_pnl).sum()
+}
+
+/// Check the day's PnL of `account` on `market_type`, or its PnL since the last reset, before an
+/// entry on `symbol` or on the timer without one. Returns the reason when a limit is hit, the kill
+/// switch then stays tripped until it is reset or the UTC day ends. Only positions settled in USDT
+/// or USDC count, see `settled_in_usd`. Fails with `Unsupported` when the connector cannot list the
+/// positions or their closed PnL.
+pub async fn check(market: &dyn MarketApi, market_type: MarketType, account: &str, symbol: Option<&str>) -> ExchangeResult<Option<String>> {
+    let (symbols, since, unrealised_at_reset) = with_state(account, |state| {
+        if let Some(symbol) = symbol {
+            state.symbols.insert(symbol.to_string());
+        }
+        let unrealised_at_reset = state.unrealised_at_reset.get(&market_type).copied().unwrap_or_default();
+        (state.symbols.clone(), state.reset_at.unwrap_or(state.day * DAY_MS), unrealised_at_reset)
+    });
+    let (loss_limit, max_losses) = (daily_loss_limit(), max_consecutive_losses());
+    if loss_limit.is_none() && max_losses.is_none() {
+        return Ok(None);
+    }
+
+    let positions = usd_positions(market).await?;
+    let mut closed = match market.closed_pnl(None, since).await {
+        Ok(list) => list,
+        // Listed by symbol, those entered since the start and those in position.
+        Err(ExchangeError::Unsupported(_)) => {
+            let symbols: HashSet<&str> = symbols.iter().map(String::as_str)
+                .filter(|symbol| settled_in_usd(symbol))
+                .chain(positions.iter().map(|position| position.symbol.as_str()))
+                .collect();
+            let mut closed = Vec::new();
+            for symbol in symbols {
+                closed.extend(market.closed_pnl(Some(symbol), since).await?);
+            }
+            closed
+        }
+        Err(e) => return Err(e),
+    };
+    closed.retain(|closed| closed.closed_at >= since && settled_in_usd(&closed.symbol));
+    closed.sort_by_key(|closed| closed.closed_at);
+
+    let realised = closed.iter().map(|closed| closed.pnl).sum::<Decimal>();
+    // What the positions open at a reset had lost by then was counted before it.
+    let unrealised = unrealised(&positions) - unrealised_at_reset;
+    let pnl = realised + unrealised;
+    let losses = closed.iter().rev().take_while(|closed| closed.pnl < Decimal::ZERO).count();
+    info!("Daily PnL account:{} since:{} realised:{} unrealised:{} losses_in_a_row:{}", account, since, realised, unrealised, losses);
+
+    let reason = match (loss_limit, max_losses) {
+        (Some(limit), _) if pnl <= -limit => Some(format!("daily PnL {} reached DAILY_LOSS_LIMIT {}", pnl, limit)),
+        (_, Some(max)) if losses >= max => Some(format!("{} losses in a row reached MAX_CONSECUTIVE_LOSSES {}", losses, max)),
+        _ => None,
+    };
+    if let Some(reason) = &reason {
+        with_state(account, |state| state.tripped = Some(reason.clone()));
+    }
+    Ok(reason)
+}
@@ -1,6 +1,6 @@
 use std::io;
 
-use actix_web::{App, HttpServer};
+use actix_web::{App, HttpServer, rt, web};
 use log::info;
 use simplelog::{Config, LevelFilter, SimpleLogger};
 
@@ -8,6 +8,11 @@ mod exchange;
 mod common;
 mod robot;
 mod rest_api;
+mod watcher;
+mod entry;
+mod sizing;
+mod risk;
+mod kill_switch;
 
 #[actix_web::main]
 async fn main() -> io::Result<()> {
@@ -19,9 +24,18 @@ async fn main() -> io::Result<()> {
 
 async fn start_http_server() -> io::Result<()> {
     info!("Start http server");
-    HttpServer::new(|| {
+    let client = exchange::http_client::build().map_err(io::Error::other)?;
+    rt::spawn(kill_switch::watch_accounts(client.clone()));
+    HttpServer::new(move || {
         App::new()
+            .app_data(web::Data::new(client.clone()))
             .service(rest_api::signal_handler)
+            .service(rest_api::open_orders_handler)
+            .service(rest_api::order_status_handler)
+            .service(rest_api::cancel_order_handler)
+            .service(rest_api::amend_order_handler)
+            .service(rest_api::kill_switch_handler)
+            .service(rest_api::kill_switch_reset_handler)
     })
         .bind("0.0.0.0:2525")?
         .run()
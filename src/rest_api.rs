@@ -1,66 +1,422 @@
+use std::future::Future;
+use std::pin::Pin;
 use std::str;
-use std::thread;
+use std::time::Duration;
 
 use actix_web::*;
-use actix_web::http::HeaderValue;
-use log::info;
+use actix_web::http::header::HeaderValue;
+use log::{error, info, warn};
+use reqwest::Client;
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde_json::Value;
+use sha256::digest;
 
-use crate::exchange::structs::OrderSide;
+use crate::exchange::account::AccountConfig;
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general;
+use crate::exchange::general::MarketApi;
+use crate::common::environments::entry_timeout_secs;
+use crate::common::utils::get_current_timestamp;
+use crate::entry::{Entry, EntryMode, Fallback};
+use crate::exchange::structs::{ContractType, MarketType, OrderSide, TrailingStop, TriggerBy};
+use crate::kill_switch;
 use crate::robot;
+use crate::robot::{CloseSignal, Operation, TakeProfit, TradeSignal};
+use crate::sizing::Sizing;
+use crate::watcher::StopRule;
+
+/// Longest signal ID, leaves room for the leg suffix within every exchange's client order ID limit.
+const SIGNAL_ID_LEN: usize = 20;
 
 #[derive(Deserialize)]
 pub struct Signal {
+    pub id: Option<String>,
     #[serde(default)]
     pub symbol: String,
+    /// `LONG`, `SHORT`, `CLOSE`, `CLOSE_LONG`, `CLOSE_SHORT`, `PARTIAL_CLOSE` or `CANCEL_ORDERS`.
     pub operation: String,
-    pub price: f64,
-    pub take_profit: f64,
-    pub stop_loss: f64,
-    pub leverage: i32,
+    /// Entry price, only entries need it.
+    #[serde(default)]
+    pub price: Decimal,
+    /// Entry order: `MARKET` (default), `LIMIT` or `POST_ONLY` at `price`.
+    pub entry: Option<String>,
+    /// Seconds a limit entry is given to fill, `ENTRY_TIMEOUT_SECS` by default.
+    pub entry_timeout: Option<u64>,
+    /// Unfilled part of a limit entry at the timeout: `CANCEL` (default) or `MARKET`.
+    pub entry_fallback: Option<String>,
+    pub take_profit: Option<Decimal>,
+    /// Take profit ladder as `price:percent` levels, e.g. `110:40,120:30,130:30`.
+    pub take_profits: Option<String>,
+    pub stop_loss: Option<Decimal>,
+    pub leverage: Option<i32>,
+    /// Entry size: `BALANCE` (default), `NOTIONAL:<usd>`, `QTY:<qty>`, `EQUITY:<percent>` or `RISK:<percent>`,
+    /// the account's `SIZING` when unset.
+    pub sizing: Option<String>,
+    /// Share of the position a `PARTIAL_CLOSE` closes.
+    pub percent: Option<Decimal>,
+    /// Stop moves as take profit levels fill: `FIXED` (default), `BREAKEVEN` or `TRAIL`.
+    pub stop_rule: Option<String>,
+    /// Trailing stop distance in price, armed at `trailing_active` when given.
+    pub trailing_stop: Option<Decimal>,
+    pub trailing_active: Option<Decimal>,
+    /// Price the exits trigger on: `LAST`, `MARK` or `INDEX`.
+    pub trigger_by: Option<String>,
+    pub exchange: Option<String>,
+    pub market_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AccountQuery {
+    pub exchange: Option<String>,
+    /// Market whose open positions a kill switch reset takes as its baseline, besides those entered.
+    pub market_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct OrderQuery {
+    pub symbol: String,
+    pub order_id: Option<String>,
+    pub order_link_id: Option<String>,
+    pub price: Option<Decimal>,
+    pub qty: Option<Decimal>,
+    pub exchange: Option<String>,
+    pub market_type: Option<String>,
 }
 
 #[get("/api/signal")]
-pub async fn signal_handler(request: HttpRequest) -> impl Responder {
-    let signal = web::Query::<Signal>::from_query(request.query_string()).unwrap();
+pub async fn signal_handler(request: HttpRequest, client: web::Data<Client>) -> impl Responder {
+    let signal = match web::Query::<Signal>::from_query(request.query_string()) {
+        Ok(signal) => signal,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid signal: {}", e)),
+    };
 
     let header: Option<&HeaderValue> = request.headers().get("METADATA");
 
-    if header == None {
-        return HttpResponse::NoContent().body("Please send metadata as header.");
+    if header.is_none() {
+        HttpResponse::NoContent().body("Please send metadata as header.")
     } else {
-        let metadata = extract_metadata(header);
+        let account = match extract_metadata(header).and_then(|metadata| AccountConfig::from_metadata(&metadata, signal.exchange.as_deref())) {
+            Ok(account) => account,
+            Err(e) => return HttpResponse::BadRequest().body(e),
+        };
 
+        let operation = match signal.operation.parse::<Operation>() {
+            Ok(operation) => operation,
+            Err(e) => return HttpResponse::BadRequest().body(e),
+        };
 
-        let side = if signal.operation.to_uppercase().eq("SHORT") { OrderSide::Short } else { OrderSide::Long };
+        let symbol: String = get_symbol(&signal.symbol);
+        let market_type = match get_market_type(signal.market_type.as_deref(), &symbol) {
+            Ok(market_type) => market_type,
+            Err(e) => return HttpResponse::BadRequest().body(e),
+        };
+        let market = match general::market(&account, market_type, client.get_ref().clone()) {
+            Ok(market) => market,
+            Err(e) => return HttpResponse::BadRequest().body(e),
+        };
 
-        let symbol: String = get_symbol(&signal);
-        let price = signal.price;
-        let tpp = signal.take_profit;
-        let slp = signal.stop_loss;
-        let leverage = signal.leverage;
-
-        let msg = format!("Receive signal symbol:{} side:{} price:{} tpp:{} slp:{}", symbol, &side, price, tpp, slp);
+        let signal_id = signal_id(&signal);
+        let prefix = format!("Receive signal id:{} exchange:{} market:{} symbol:{}", signal_id, account.exchange, market_type, symbol);
+        let (msg, task): (String, Pin<Box<dyn Future<Output = ExchangeResult<()>>>>) = match operation {
+            Operation::Open(side) => {
+                if let Some(reason) = kill_switch::tripped(&kill_switch::account_key(&account)) {
+                    return HttpResponse::Forbidden().body(format!("Kill switch tripped: {}", reason));
+                }
+                let trade_signal = match get_trade_signal(&signal, &signal_id, &symbol, side, &account, market_type) {
+                    Ok(trade_signal) => trade_signal,
+                    Err(e) => return HttpResponse::BadRequest().body(e),
+                };
+                if trade_signal.entry.mode != EntryMode::Market && !market.tracks_orders() {
+                    return HttpResponse::BadRequest().body(format!("{:?} entries are not supported on {} {}", trade_signal.entry.mode, account.exchange, market_type));
+                }
+                if trade_signal.trailing_stop.is_some() && !market.trails_stops() {
+                    return HttpResponse::BadRequest().body(format!("Trailing stops are not supported on {} {}", account.exchange, market_type));
+                }
+                if let Some(trigger_by) = trade_signal.trigger_by.filter(|&trigger_by| !market.triggers_by(trigger_by)) {
+                    return HttpResponse::BadRequest().body(format!("{:?} trigger price is not supported on {} {}", trigger_by, account.exchange, market_type));
+                }
+                kill_switch::watch(&account, market_type);
+                let msg = format!("{} side:{} price:{} tpp:{} slp:{}", prefix, &side, trade_signal.price, format_take_profits(&trade_signal.take_profits), trade_signal.stop_loss);
+                (msg, Box::pin(robot::trade(market, trade_signal)))
+            }
+            Operation::Close(side) => {
+                let close_signal = CloseSignal { id: signal_id, symbol: symbol.to_string(), side, percent: Decimal::ONE_HUNDRED };
+                (format!("{} operation:{}", prefix, signal.operation), Box::pin(robot::close(market, close_signal)))
+            }
+            Operation::PartialClose => {
+                let percent = match signal.percent {
+                    Some(percent) if percent > Decimal::ZERO && percent <= Decimal::ONE_HUNDRED => percent,
+                    _ => return HttpResponse::BadRequest().body("PARTIAL_CLOSE needs a percent above 0 and up to 100"),
+                };
+                let close_signal = CloseSignal { id: signal_id, symbol: symbol.to_string(), side: None, percent };
+                (format!("{} operation:{} percent:{}", prefix, signal.operation, percent), Box::pin(robot::close(market, close_signal)))
+            }
+            Operation::CancelOrders => (format!("{} operation:{}", prefix, signal.operation), Box::pin(robot::cancel_orders(market, symbol.to_string()))),
+        };
         info!("{}", msg);
 
-        thread::spawn(move || {
-            robot::trade(symbol, side, price, tpp, slp, leverage, metadata);
+        rt::spawn(async move {
+            if let Err(e) = task.await {
+                if e.is_retryable() {
+                    warn!("Signal interrupted symbol:{} {}", symbol, e);
+                } else {
+                    error!("Signal failed symbol:{} {}", symbol, e);
+                }
+            }
         });
         HttpResponse::Ok().body(msg)
     }
 }
 
-fn extract_metadata(header: Option<&HeaderValue>) -> Value {
-    let metadata_header = base64::decode(header.unwrap());
-    let json = String::from_utf8_lossy(metadata_header.unwrap().as_slice()).to_string();
-    let metadata: serde_json::error::Result<Value> = serde_json::from_str(json.as_str());
-    metadata.unwrap()
+/// The entry signal with its exits, rejected when they are missing or on the wrong side of the price.
+fn get_trade_signal(signal: &Signal, signal_id: &str, symbol: &str, side: OrderSide, account: &AccountConfig, market_type: MarketType) -> Result<TradeSignal, String> {
+    let price = signal.price;
+    let stop_loss = signal.stop_loss.ok_or("stop_loss is required")?;
+    let leverage = signal.leverage.ok_or("leverage is required")?;
+    if price <= Decimal::ZERO {
+        return Err(String::from("price must be positive"));
+    }
+    let entry = get_entry(signal)?;
+    let sizing = signal.sizing.as_deref().or(account.sizing.as_deref()).map(str::parse::<Sizing>).transpose()?.unwrap_or(Sizing::Balance);
+    let take_profits = get_take_profits(signal.take_profit, signal.take_profits.as_deref())?;
+    let stop_rule = signal.stop_rule.as_deref().map(str::parse::<StopRule>).transpose()?.unwrap_or(StopRule::Fixed);
+    let trigger_by = signal.trigger_by.as_deref().map(str::parse::<TriggerBy>).transpose()?;
+    let trailing_stop = match (signal.trailing_stop, signal.trailing_active) {
+        (Some(distance), _) if distance <= Decimal::ZERO => return Err(String::from("trailing_stop must be positive")),
+        (Some(distance), active_price) => Some(TrailingStop { distance, active_price }),
+        (None, Some(_)) => return Err(String::from("trailing_active needs trailing_stop")),
+        (None, None) => None,
+    };
+    let trade_signal = TradeSignal { id: signal_id.to_string(), account: kill_switch::account_key(account), market_type, symbol: symbol.to_string(), side, price, entry, take_profits, stop_loss, leverage, sizing, stop_rule, trailing_stop, trigger_by };
+    trade_signal.check_exits(price).map_err(|e| e.to_string())?;
+    Ok(trade_signal)
+}
+
+/// Open and conditional orders on `symbol`.
+#[get("/api/orders")]
+pub async fn open_orders_handler(request: HttpRequest, client: web::Data<Client>) -> impl Responder {
+    let (query, market) = match order_market(&request, &client) {
+        Ok(resolved) => resolved,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    match market.open_orders(&query.symbol).await {
+        Ok(orders) => HttpResponse::Ok().json(orders),
+        Err(e) => exchange_error_response(e),
+    }
+}
+
+#[get("/api/order")]
+pub async fn order_status_handler(request: HttpRequest, client: web::Data<Client>) -> impl Responder {
+    let (query, market) = match order_market(&request, &client) {
+        Ok(resolved) => resolved,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let result = match (query.order_id.as_deref(), query.order_link_id.as_deref()) {
+        (Some(order_id), _) => market.order_status(&query.symbol, order_id).await,
+        (None, Some(order_link_id)) => match market.find_order(&query.symbol, order_link_id).await {
+            Ok(Some(order)) => Ok(order),
+            Ok(None) => return HttpResponse::NotFound().body(format!("Order {} not found", order_link_id)),
+            Err(e) => Err(e),
+        },
+        (None, None) => return HttpResponse::BadRequest().body("order_id or order_link_id is required"),
+    };
+    match result {
+        Ok(order) => HttpResponse::Ok().json(order),
+        Err(e) => exchange_error_response(e),
+    }
+}
+
+/// Cancel `order_id`, or every open order on `symbol` when it is missing.
+#[post("/api/order/cancel")]
+pub async fn cancel_order_handler(request: HttpRequest, client: web::Data<Client>) -> impl Responder {
+    let (query, market) = match order_market(&request, &client) {
+        Ok(resolved) => resolved,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let result = match query.order_id.as_deref() {
+        Some(order_id) => market.cancel_order(&query.symbol, order_id).await,
+        None => market.cancel_all(&query.symbol).await,
+    };
+    match result {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => exchange_error_response(e),
+    }
+}
+
+#[post("/api/order/amend")]
+pub async fn amend_order_handler(request: HttpRequest, client: web::Data<Client>) -> impl Responder {
+    let (query, market) = match order_market(&request, &client) {
+        Ok(resolved) => resolved,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let order_id = match query.order_id.as_deref() {
+        Some(order_id) => order_id,
+        None => return HttpResponse::BadRequest().body("order_id is required"),
+    };
+    if query.price.is_none() && query.qty.is_none() {
+        return HttpResponse::BadRequest().body("price or qty is required");
+    }
+    match market.amend_order(&query.symbol, order_id, query.price, query.qty).await {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => exchange_error_response(e),
+    }
 }
 
-fn get_symbol(signal: &web::Query<Signal>) -> String {
-    let sym_len = signal.symbol.len();
-    let perp_len = "PERP".len();
+/// Whether the kill switch stopped entries for the account today and why.
+#[get("/api/kill-switch")]
+pub async fn kill_switch_handler(request: HttpRequest) -> impl Responder {
+    let account = match request_account(&request) {
+        Ok((account, _)) => kill_switch::account_key(&account),
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let reason = kill_switch::tripped(&account);
+    HttpResponse::Ok().json(serde_json::json!({ "tripped": reason.is_some(), "reason": reason }))
+}
 
-    signal.symbol.chars().skip(0).take(sym_len - perp_len).collect()
+/// Allow entries again after the kill switch tripped.
+#[post("/api/kill-switch/reset")]
+pub async fn kill_switch_reset_handler(request: HttpRequest, client: web::Data<Client>) -> impl Responder {
+    let (account, market_type) = match request_account(&request) {
+        Ok(resolved) => resolved,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let key = kill_switch::account_key(&account);
+    let mut market_types = kill_switch::market_types(&key);
+    if let Some(market_type) = market_type.filter(|market_type| !market_types.contains(market_type)) {
+        market_types.push(market_type);
+    }
+    let mut markets = Vec::new();
+    for market_type in market_types {
+        match general::market(&account, market_type, client.get_ref().clone()) {
+            Ok(market) => markets.push((market_type, market)),
+            Err(e) => return HttpResponse::BadRequest().body(e),
+        }
+    }
+    match kill_switch::reset(&key, &markets).await {
+        Ok(()) => HttpResponse::Ok().body("Kill switch reset"),
+        Err(e) => exchange_error_response(e),
+    }
+}
+
+/// Account of a request from its `METADATA` header and `exchange` query parameter, with its
+/// `market_type` when given.
+fn request_account(request: &HttpRequest) -> Result<(AccountConfig, Option<MarketType>), String> {
+    let query = web::Query::<AccountQuery>::from_query(request.query_string())
+        .map_err(|e| format!("Invalid query: {}", e))?;
+    let header = request.headers().get("METADATA");
+    if header.is_none() {
+        return Err(String::from("Please send metadata as header."));
+    }
+    let market_type = query.market_type.as_deref().map(str::parse::<MarketType>).transpose()?;
+    let account = extract_metadata(header).and_then(|metadata| AccountConfig::from_metadata(&metadata, query.exchange.as_deref()))?;
+    Ok((account, market_type))
+}
+
+/// Parse an order management query and build the client for its account.
+fn order_market(request: &HttpRequest, client: &web::Data<Client>) -> Result<(OrderQuery, Box<dyn MarketApi>), String> {
+    let mut query = web::Query::<OrderQuery>::from_query(request.query_string())
+        .map_err(|e| format!("Invalid order query: {}", e))?
+        .into_inner();
+    let header = request.headers().get("METADATA");
+    if header.is_none() {
+        return Err(String::from("Please send metadata as header."));
+    }
+    let account = extract_metadata(header)
+        .and_then(|metadata| AccountConfig::from_metadata(&metadata, query.exchange.as_deref()))?;
+    query.symbol = get_symbol(&query.symbol);
+    let market_type = get_market_type(query.market_type.as_deref(), &query.symbol)?;
+    let market = general::market(&account, market_type, client.get_ref().clone())?;
+    Ok((query, market))
+}
+
+fn exchange_error_response(e: ExchangeError) -> HttpResponse {
+    error!("Order request failed {}", e);
+    match e {
+        ExchangeError::Unsupported(_) => HttpResponse::NotImplemented().body(e.to_string()),
+        ExchangeError::InvalidOrder(_) => HttpResponse::BadRequest().body(e.to_string()),
+        e if e.is_retryable() => HttpResponse::ServiceUnavailable().body(e.to_string()),
+        e => HttpResponse::BadGateway().body(e.to_string()),
+    }
+}
+
+fn extract_metadata(header: Option<&HeaderValue>) -> Result<Value, String> {
+    let metadata_header = base64::decode(header.unwrap()).map_err(|e| format!("Invalid metadata encoding: {}", e))?;
+    let json = String::from_utf8_lossy(metadata_header.as_slice()).to_string();
+    serde_json::from_str(json.as_str()).map_err(|e| format!("Invalid metadata json: {}", e))
+}
+
+/// The caller's `id` when given, otherwise a hash of the signal within the current minute,
+/// so a signal delivered twice maps to the same client order IDs.
+fn signal_id(signal: &Signal) -> String {
+    if let Some(id) = signal.id.as_deref() {
+        let id: String = id.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        if !id.is_empty() && id.len() <= SIGNAL_ID_LEN {
+            return id;
+        }
+    }
+    let source = match signal.id.as_deref() {
+        Some(id) => id.to_string(),
+        None => format!("{:?}|{}|{}|{}|{:?}|{:?}|{:?}|{:?}|{:?}|{}",
+                        signal.exchange, signal.symbol, signal.operation, signal.price, signal.take_profit,
+                        signal.take_profits, signal.stop_loss, signal.leverage, signal.market_type, get_current_timestamp() / 60_000),
+    };
+    digest(source)[..SIGNAL_ID_LEN].to_string()
+}
+
+/// The `take_profits` ladder, or the single `take_profit` closing the whole position.
+fn get_take_profits(take_profit: Option<Decimal>, take_profits: Option<&str>) -> Result<Vec<TakeProfit>, String> {
+    let levels = match take_profits {
+        Some(levels) => levels.split(',')
+            .map(|level| {
+                let (price, percent) = level.split_once(':').ok_or_else(|| format!("Invalid take profit level: {}", level))?;
+                Ok(TakeProfit {
+                    price: price.trim().parse().map_err(|_| format!("Invalid take profit price: {}", price))?,
+                    percent: percent.trim().parse().map_err(|_| format!("Invalid take profit percent: {}", percent))?,
+                })
+            })
+            .collect::<Result<Vec<TakeProfit>, String>>()?,
+        None => match take_profit {
+            Some(price) => vec![TakeProfit { price, percent: Decimal::ONE_HUNDRED }],
+            None => return Err(String::from("take_profit or take_profits is required")),
+        },
+    };
+    if levels.iter().any(|level| level.percent <= Decimal::ZERO) {
+        return Err(String::from("Take profit percentages must be positive"));
+    }
+    if levels.iter().map(|level| level.percent).sum::<Decimal>() != Decimal::ONE_HUNDRED {
+        return Err(String::from("Take profit percentages must add up to 100"));
+    }
+    Ok(levels)
+}
+
+/// How the signal enters.
+fn get_entry(signal: &Signal) -> Result<Entry, String> {
+    let mode = signal.entry.as_deref().map(str::parse::<EntryMode>).transpose()?.unwrap_or(EntryMode::Market);
+    let fallback = signal.entry_fallback.as_deref().map(str::parse::<Fallback>).transpose()?.unwrap_or(Fallback::Cancel);
+    let timeout = Duration::from_secs(signal.entry_timeout.unwrap_or_else(entry_timeout_secs));
+    Ok(Entry { mode, timeout, fallback })
+}
+
+fn format_take_profits(levels: &[TakeProfit]) -> String {
+    levels.iter().map(|level| format!("{}:{}", level.price, level.percent)).collect::<Vec<String>>().join(",")
+}
+
+fn get_symbol(symbol: &str) -> String {
+    symbol.strip_suffix("PERP").unwrap_or(symbol).to_string()
+}
+
+/// `market_type` defaults to the derivative the symbol names, linear or inverse.
+fn get_market_type(market_type: Option<&str>, symbol: &str) -> Result<MarketType, String> {
+    let inverse = ContractType::from_symbol(symbol).is_inverse();
+    let market_type = match market_type {
+        Some(market_type) => market_type.parse::<MarketType>()?,
+        None if inverse => MarketType::Inverse,
+        None => MarketType::Linear,
+    };
+    match market_type {
+        MarketType::Linear if inverse => Err(format!("{} is an inverse contract", symbol)),
+        MarketType::Inverse if !inverse => Err(format!("{} is not an inverse contract", symbol)),
+        market_type => Ok(market_type),
+    }
 }
\ No newline at end of file
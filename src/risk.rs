@@ -0,0 +1,71 @@
+use std::collections::HashSet;
+
+use rust_decimal::Decimal;
+
+use crate::common::environments::{concurrent_positions, correlation_groups, max_group_exposure, max_group_positions, max_symbol_exposure, max_total_exposure};
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::MarketApi;
+use crate::exchange::structs::PositionInformation;
+
+/// The limit an entry worth `notional` USD on `symbol` would breach, `None` when it stays within
+/// all of them. On connectors that cannot list their positions the limits cannot be checked and
+/// the entry is refused.
+pub async fn breach(market: &dyn MarketApi, symbol: &str, notional: Decimal) -> ExchangeResult<Option<String>> {
+    let limited = concurrent_positions().is_some() || max_total_exposure().is_some() || max_symbol_exposure().is_some()
+        || max_group_positions().is_some() || max_group_exposure().is_some();
+    if !limited {
+        return Ok(None);
+    }
+    let positions = match market.positions().await {
+        Ok(positions) => positions,
+        Err(ExchangeError::Unsupported(_)) => return Ok(Some(String::from("positions cannot be listed to check the limits"))),
+        Err(e) => return Err(e),
+    };
+    Ok(check(&positions, symbol, notional))
+}
+
+fn check(positions: &[PositionInformation], symbol: &str, notional: Decimal) -> Option<String> {
+    let (count, value) = exposure(positions, symbol, notional, |_| true);
+    if let Some(max) = concurrent_positions().filter(|max| count > *max) {
+        return Some(format!("{} open positions exceed CONCURRENT_POSITION {}", count, max));
+    }
+    if let Some(max) = max_total_exposure().filter(|max| value > *max) {
+        return Some(format!("total exposure {} exceeds MAX_TOTAL_EXPOSURE {}", value, max));
+    }
+
+    let (_, value) = exposure(positions, symbol, notional, |other| other == symbol);
+    if let Some(max) = max_symbol_exposure().filter(|max| value > *max) {
+        return Some(format!("{} exposure {} exceeds MAX_SYMBOL_EXPOSURE {}", symbol, value, max));
+    }
+
+    if let Some((name, members)) = group(symbol) {
+        let (count, value) = exposure(positions, symbol, notional, |other| members.iter().any(|member| member == other));
+        if let Some(max) = max_group_positions().filter(|max| count > *max) {
+            return Some(format!("{} open positions in group {} exceed MAX_GROUP_POSITIONS {}", count, name, max));
+        }
+        if let Some(max) = max_group_exposure().filter(|max| value > *max) {
+            return Some(format!("group {} exposure {} exceeds MAX_GROUP_EXPOSURE {}", name, value, max));
+        }
+    }
+    None
+}
+
+/// Symbols holding a position and their value in USD once the entry on `symbol` is added,
+/// counting only the positions `include` selects.
+fn exposure(positions: &[PositionInformation], symbol: &str, notional: Decimal, include: impl Fn(&str) -> bool) -> (usize, Decimal) {
+    let included = || positions.iter().filter(|position| include(&position.symbol));
+    let mut symbols: HashSet<&str> = included().map(|position| position.symbol.as_str()).collect();
+    symbols.insert(symbol);
+    let value = included().map(PositionInformation::notional).sum::<Decimal>() + notional;
+    (symbols.len(), value)
+}
+
+/// Name and symbols of the correlation group `symbol` belongs to.
+fn group(symbol: &str) -> Option<(String, Vec<String>)> {
+    let symbol = symbol.to_uppercase();
+    correlation_groups()
+        .split(';')
+        .filter_map(|group| group.split_once(':'))
+        .map(|(name, members)| (name.trim().to_string(), members.split(',').map(|member| member.trim().to_uppercase()).collect::<Vec<String>>()))
+        .find(|(_, members)| members.contains(&symbol))
+}
@@ -1,60 +1,429 @@
-use log::{debug, info};
-use serde_json::Value;
+use std::time::Duration;
 
-use crate::exchange::bybit::Market;
+use log::{error, info, warn};
+use rust_decimal::Decimal;
+
+use crate::exchange::error::{ExchangeError, ExchangeResult};
 use crate::exchange::general::MarketApi;
-use crate::exchange::structs::{Order, OrderSide, OrderType, TimeInForce};
-
-pub fn trade(symbol: String, side: OrderSide, price: f64, take_profit: f64, stop_loss: f64, leverage: i32, metadata: Value) {
-    let coin = String::from("USDT");
-    let available_balance = Market::wallet_available_balance(coin, &metadata);
-    let is_in_position = Market::is_in_position(&symbol, &metadata);
-    info!("Available balance USDT:{}",available_balance);
-
-    if available_balance > 10.0 && !is_in_position {
-        info!("Switch to Isolated");
-        let isolated_changed = Market::switch_isolated(&symbol, true, leverage, &metadata);
-
-        info!("Change Leverage");
-        let leverage_changed = Market::leverage(&symbol, leverage, &metadata);
-
-        if isolated_changed && leverage_changed {
-            let base = available_balance * leverage as f64 / price;
-            let qty = format!("{:.4}", base).parse::<f64>().unwrap();
-            info!("Order size:{}", qty);
-
-            let order = Order {
-                symbol: symbol.to_string(),
-                time_in_force: TimeInForce::GoodTillCancel,
-                price: 0.0,
-                qty,
-                reduce_only: Some(false),
-                close_on_trigger: Some(false),
-                order_type: OrderType::Market,
-                leverage: Some(20),
-                side,
-                take_profit: None,
-                stop_loss: None,
-            };
-
-            info!("Send order symbol:{} tpp:{} slp:{}",&symbol,&take_profit,&stop_loss);
-            if Market::order(order, &metadata) {
-                info!("Get position information symbol:{}",&symbol);
-                let pi = Market::position(&symbol, &metadata).unwrap();
-                if &pi.entry_price > &0.0 {
-                    let size = Option::Some(pi.size);
-
-                    info!("Set stop loss symbol:{} side:{}",&symbol,&side);
-                    Market::stop_loss(&symbol, size, &side, Option::None, Option::Some(stop_loss), &metadata);
-
-                    info!("Set take profit symbol:{} qty:{}",&symbol,size.unwrap());
-                    Market::take_profit(&symbol, size, &side, Option::Some(take_profit), Option::None, &metadata);
+use crate::common::environments::{entry_poll_interval_secs, kill_switch_flatten, opposite_signal_policy};
+use crate::entry;
+use crate::entry::{Entry, EntryMode};
+use crate::exchange::structs::{ContractType, ExitOptions, MarketType, Order, OrderSide, OrderType, PositionInformation, TimeInForce, TrailingStop, TriggerBy};
+use crate::kill_switch;
+use crate::risk;
+use crate::sizing;
+use crate::sizing::Sizing;
+use crate::watcher;
+use crate::watcher::{Exits, StopRule};
+
+/// A trade signal, `id` is stable across retries of the same signal.
+pub struct TradeSignal {
+    pub id: String,
+    /// Account the kill switch is kept for, see `kill_switch::account_key`.
+    pub account: String,
+    pub market_type: MarketType,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub entry: Entry,
+    pub take_profits: Vec<TakeProfit>,
+    pub stop_loss: Decimal,
+    pub leverage: i32,
+    pub sizing: Sizing,
+    pub stop_rule: StopRule,
+    pub trailing_stop: Option<TrailingStop>,
+    /// Price the exits trigger on, the exchange default when `None`.
+    pub trigger_by: Option<TriggerBy>,
+}
+
+/// What a signal asks for.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Enter a position on this side.
+    Open(OrderSide),
+    /// Close the position, only when it is on the given side.
+    Close(Option<OrderSide>),
+    /// Close part of the position.
+    PartialClose,
+    /// Cancel the open orders, the position stays.
+    CancelOrders,
+}
+
+impl std::str::FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "LONG" | "BUY" => Ok(Operation::Open(OrderSide::Long)),
+            "SHORT" | "SELL" => Ok(Operation::Open(OrderSide::Short)),
+            "CLOSE" => Ok(Operation::Close(None)),
+            "CLOSE_LONG" => Ok(Operation::Close(Some(OrderSide::Long))),
+            "CLOSE_SHORT" => Ok(Operation::Close(Some(OrderSide::Short))),
+            "PARTIAL_CLOSE" => Ok(Operation::PartialClose),
+            "CANCEL_ORDERS" => Ok(Operation::CancelOrders),
+            _ => Err(format!("Unsupported operation: {}", s)),
+        }
+    }
+}
+
+/// A signal reducing the position on `symbol` by `percent`, 100 closes it.
+pub struct CloseSignal {
+    pub id: String,
+    pub symbol: String,
+    pub side: Option<OrderSide>,
+    pub percent: Decimal,
+}
+
+/// What a signal does when the symbol holds a position on the other side.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OppositePolicy {
+    /// Leave the position, the signal is dropped.
+    Ignore,
+    /// Close the position without opening the new side.
+    Close,
+    /// Close the position and open the new side with its own exits.
+    Reverse,
+}
+
+impl std::str::FromStr for OppositePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "IGNORE" => Ok(OppositePolicy::Ignore),
+            "CLOSE" | "CLOSE_ONLY" => Ok(OppositePolicy::Close),
+            "REVERSE" | "CLOSE_AND_REVERSE" => Ok(OppositePolicy::Reverse),
+            _ => Err(format!("Unsupported opposite signal policy: {}", s)),
+        }
+    }
+}
+
+impl TradeSignal {
+    /// Check the stop loss and every take profit level against an entry at `entry`.
+    pub fn check_exits(&self, entry: Decimal) -> ExchangeResult<()> {
+        self.side.check_exits(entry, None, Some(self.stop_loss))?;
+        check_take_profits(&self.side, entry, &self.take_profits)
+    }
+}
+
+/// Check every level of a take profit ladder against an entry at `entry`.
+fn check_take_profits(side: &OrderSide, entry: Decimal, take_profits: &[TakeProfit]) -> ExchangeResult<()> {
+    take_profits.iter().try_for_each(|level| side.check_exits(entry, Some(level.price), None))
+}
+
+/// One level of a take profit ladder, closing `percent` of the position at `price`.
+#[derive(Clone, Debug)]
+pub struct TakeProfit {
+    pub price: Decimal,
+    pub percent: Decimal,
+}
+
+/// Order placed for a signal.
+pub enum Leg {
+    Entry,
+    /// Reduce-only market order closing all or part of the position.
+    Close,
+    /// Market order closing open position `n` when the kill switch trips.
+    Flatten(u32),
+    /// Market order for what a limit entry left unfilled.
+    FallbackEntry,
+    TakeProfit(u32),
+    StopLoss,
+    /// Stop moved after take profit level `n` filled.
+    MovedStop(u32),
+}
+
+impl Leg {
+    /// Client order ID of this leg, the signal ID followed by the leg name such as `tp1`.
+    pub fn link_id(&self, signal_id: &str) -> String {
+        let leg = match self {
+            Leg::Entry => String::from("entry"),
+            Leg::FallbackEntry => String::from("entry2"),
+            Leg::Close => String::from("close"),
+            Leg::Flatten(n) => format!("flat{}", n),
+            Leg::TakeProfit(n) => format!("tp{}", n),
+            Leg::StopLoss => String::from("sl"),
+            Leg::MovedStop(n) => format!("sl{}", n),
+        };
+        format!("{}{}", signal_id, leg)
+    }
+}
+
+pub async fn trade(market: Box<dyn MarketApi>, signal: TradeSignal) -> ExchangeResult<()> {
+    signal.check_exits(signal.price)?;
+    let TradeSignal { id, account, market_type, symbol, side, price, entry, take_profits, stop_loss, leverage, sizing, stop_rule, trailing_stop, trigger_by } = signal;
+    let leverage = sizing::leverage(leverage);
+    let entry_link = Leg::Entry.link_id(&id);
+    // A retried signal finds its entry already on the exchange.
+    match market.find_order(&symbol, &entry_link).await {
+        Ok(Some(order)) => {
+            info!("Signal already placed signal:{} symbol:{} order_id:{} status:{:?}", &id, &symbol, &order.order_id, order.status);
+            return Ok(());
+        }
+        Ok(None) | Err(ExchangeError::Unsupported(_)) => {}
+        Err(e) if e.is_retryable() => return Err(e),
+        Err(e) => warn!("Lookup of order {} failed symbol:{} {}", &entry_link, &symbol, e),
+    }
+
+    if market.is_in_position(&symbol).await? {
+        let policy = opposite_signal_policy(&symbol, OppositePolicy::Ignore);
+        match market.position(&symbol).await? {
+            Some(position) if position.side != side && policy != OppositePolicy::Ignore => {
+                close_position(market.as_ref(), &position, &Leg::Close.link_id(&id)).await?;
+                if policy == OppositePolicy::Close {
+                    return Ok(());
                 }
-            } else {
-                debug!("Market Order not completed")
             }
+            _ => {
+                info!("Already in position, signal ignored symbol:{} side:{} policy:{:?}", &symbol, &side, policy);
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(reason) = kill_switch::tripped(&account) {
+        warn!("Kill switch tripped, entry skipped signal:{} symbol:{} {}", &id, &symbol, reason);
+        return Ok(());
+    }
+    match kill_switch::check(market.as_ref(), market_type, &account, Some(&symbol)).await {
+        Ok(Some(reason)) => {
+            error!("Kill switch tripped account:{} {}", &account, reason);
+            if kill_switch_flatten() {
+                flatten(market.as_ref(), &id).await?;
+            }
+            return Ok(());
+        }
+        Ok(None) => {}
+        // Limits that are set but cannot be evaluated stop the entry as a hit one would.
+        Err(ExchangeError::Unsupported(e)) => {
+            error!("Kill switch cannot be checked, entry refused signal:{} symbol:{} {}", &id, &symbol, e);
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    }
+
+    let contract_type = market.contract_type(&symbol);
+    if contract_type == ContractType::Spot && matches!(side, OrderSide::Short) {
+        warn!("Spot cannot open a short symbol:{}", &symbol);
+        return Ok(());
+    }
+    let coin = contract_type.margin_coin(&symbol);
+    let available_balance = market.wallet_available_balance(&coin).await?;
+    info!("Available balance {}:{}", &coin, available_balance);
+
+    if contract_type.balance_value(available_balance, price) > Decimal::TEN {
+        let qty = sizing.order_qty(contract_type, available_balance, leverage, price, stop_loss);
+        info!("Order size:{} sizing:{:?}", qty, sizing);
+        if qty <= Decimal::ZERO {
+            warn!("Order size is zero symbol:{} sizing:{:?}", &symbol, sizing);
+            return Ok(());
+        }
+        if let Some(reason) = risk::breach(market.as_ref(), &symbol, contract_type.notional(qty, price)).await? {
+            warn!("Signal rejected by risk limits signal:{} symbol:{} {}", &id, &symbol, reason);
+            return Ok(());
+        }
+
+        // Spot margin is always cross so it skips the isolated switch, but it is leveraged like derivatives.
+        if !contract_type.is_spot() {
+            info!("Switch to Isolated");
+            market.switch_isolated(&symbol, true, leverage).await?;
+        }
+
+        if contract_type != ContractType::Spot {
+            info!("Change Leverage");
+            market.leverage(&symbol, leverage).await?;
+        }
+
+        let order = Order {
+            symbol: symbol.to_string(),
+            time_in_force: entry.mode.time_in_force(),
+            price: if entry.mode == EntryMode::Market { Decimal::ZERO } else { price },
+            qty,
+            reduce_only: Some(false),
+            close_on_trigger: Some(false),
+            order_type: entry.mode.order_type(),
+            leverage: Some(leverage),
+            side,
+            take_profit: None,
+            stop_loss: None,
+            order_link_id: Some(entry_link.clone()),
+        };
+
+        info!("Send order signal:{} symbol:{} link:{} tpp:{:?} slp:{}",&id,&symbol,&entry_link,&take_profits,&stop_loss);
+        let filled = match entry::enter(market.as_ref(), &entry, order, &id).await {
+            Err(ExchangeError::InsufficientBalance { code, message }) => {
+                warn!("Order rejected for balance symbol:{} {}:{}", &symbol, code, message);
+                return Ok(());
+            }
+            result => result?,
+        };
+        if filled == Some(Decimal::ZERO) {
+            info!("Entry not filled, no exits symbol:{} link:{}", &symbol, &entry_link);
+            return Ok(());
+        }
+
+        info!("Get position information symbol:{}",&symbol);
+        if let Some(pi) = market.position(&symbol).await? {
+            if pi.entry_price > Decimal::ZERO {
+                // Size the exits on what the entry filled when the exchange reports it.
+                let size = filled.unwrap_or(pi.size);
+
+                // The fill may have slipped past an exit, such a leg is not sent.
+                let sl_link = Leg::StopLoss.link_id(&id);
+                info!("Set stop loss symbol:{} side:{} link:{}",&symbol,&side,&sl_link);
+                let result = match side.check_exits(pi.entry_price, None, Some(stop_loss)) {
+                    Ok(()) => market.stop_loss(&symbol, Some(size), &side, Option::None, Option::Some(stop_loss), ExitOptions { order_link_id: Some(&sl_link), trigger_by }).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    error!("Set stop loss failed symbol:{} {}", &symbol, e);
+                }
+
+                if let Some(trailing_stop) = &trailing_stop {
+                    info!("Set trailing stop symbol:{} distance:{} active:{:?}", &symbol, trailing_stop.distance, trailing_stop.active_price);
+                    if let Err(e) = market.trailing_stop(&symbol, &side, trailing_stop).await {
+                        error!("Set trailing stop failed symbol:{} {}", &symbol, e);
+                    }
+                }
+
+                // A ladder is placed whole or not at all, checked before its first level is sent.
+                let step = market.qty_step(&symbol).await?;
+                let mut levels = Vec::new();
+                match check_take_profits(&side, pi.entry_price, &take_profits) {
+                    Ok(()) => for (n, level, qty) in ladder(&take_profits, size, step) {
+                        let tp_link = Leg::TakeProfit(n).link_id(&id);
+                        info!("Set take profit symbol:{} price:{} qty:{} link:{}",&symbol,level.price,qty,&tp_link);
+                        market.take_profit(&symbol, Some(qty), &side, Option::Some(level.price), Option::None, ExitOptions { order_link_id: Some(&tp_link), trigger_by }).await?;
+                        levels.push((n, level.clone()));
+                    },
+                    Err(e) => error!("Take profits not set symbol:{} {}", &symbol, e),
+                }
+
+                if stop_rule != StopRule::Fixed && levels.len() > 1 {
+                    info!("Watch exits symbol:{} rule:{:?}", &symbol, stop_rule);
+                    let exits = Exits { signal_id: &id, symbol: &symbol, side, entry_price: pi.entry_price, stop_rule, trigger_by, levels };
+                    watcher::watch(market.as_ref(), exits).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Close `percent` of the position on `symbol`, all of it also cancels its exits. A signal naming a
+/// side leaves a position on the other side alone.
+pub async fn close(market: Box<dyn MarketApi>, signal: CloseSignal) -> ExchangeResult<()> {
+    let CloseSignal { id, symbol, side, percent } = signal;
+    let position = match market.position(&symbol).await? {
+        Some(position) if market.is_in_position(&symbol).await? => position,
+        _ => {
+            info!("No position to close symbol:{}", &symbol);
+            return Ok(());
+        }
+    };
+    if side.is_some_and(|side| side != position.side) {
+        info!("Position is on the other side, close ignored symbol:{} side:{}", &symbol, &position.side);
+        return Ok(());
+    }
+    if percent >= Decimal::ONE_HUNDRED {
+        return close_position(market.as_ref(), &position, &Leg::Close.link_id(&id)).await;
+    }
+
+    let step = market.qty_step(&symbol).await?;
+    let qty = floor_to_step(position.size * percent / Decimal::ONE_HUNDRED, step);
+    if qty <= Decimal::ZERO {
+        warn!("Partial close below one lot symbol:{} size:{} percent:{}", &symbol, position.size, percent);
+        return Ok(());
+    }
+    info!("Partial close symbol:{} side:{} qty:{} of:{}", &symbol, &position.side, qty, position.size);
+    // Reduce-only exits larger than what is left are trimmed by the exchange.
+    reduce(market.as_ref(), &position, qty, &Leg::Close.link_id(&id)).await
+}
+
+/// Cancel every open order on `symbol`, resting entries and exits alike.
+pub async fn cancel_orders(market: Box<dyn MarketApi>, symbol: String) -> ExchangeResult<()> {
+    info!("Cancel orders symbol:{}", &symbol);
+    market.cancel_all(&symbol).await
+}
+
+/// Polls of the position after a close before it is reported as still open.
+const CLOSE_CHECKS: u32 = 5;
+
+/// Cancel the open orders of `position`, its exits, and close it with a reduce-only market order.
+async fn close_position(market: &dyn MarketApi, position: &PositionInformation, order_link_id: &str) -> ExchangeResult<()> {
+    let symbol = &position.symbol;
+    info!("Close position symbol:{} side:{} size:{}", symbol, &position.side, position.size);
+    // A position whose exits cannot be cancelled is left untouched, they would reopen it.
+    market.cancel_all(symbol).await?;
+    reduce(market, position, position.size, order_link_id).await?;
+
+    let interval = Duration::from_secs(entry_poll_interval_secs());
+    for _ in 0..CLOSE_CHECKS {
+        if !market.is_in_position(symbol).await? {
+            info!("Position closed symbol:{}", symbol);
+            return Ok(());
+        }
+        tokio::time::sleep(interval).await;
+    }
+    Err(ExchangeError::Exchange { code: -1, message: format!("Position {} still open after close", symbol) })
+}
+
+/// Close every open position of the account.
+pub async fn flatten(market: &dyn MarketApi, signal_id: &str) -> ExchangeResult<()> {
+    let positions = market.positions().await?;
+    for (n, position) in positions.iter().enumerate() {
+        if let Err(e) = close_position(market, position, &Leg::Flatten(n as u32 + 1).link_id(signal_id)).await {
+            error!("Flatten failed symbol:{} {}", &position.symbol, e);
+        }
+    }
+    Ok(())
+}
+
+/// Close `qty` of `position` with a reduce-only market order.
+async fn reduce(market: &dyn MarketApi, position: &PositionInformation, qty: Decimal, order_link_id: &str) -> ExchangeResult<()> {
+    let order = Order {
+        symbol: position.symbol.to_string(),
+        time_in_force: TimeInForce::GoodTillCancel,
+        price: Decimal::ZERO,
+        qty,
+        reduce_only: Some(true),
+        close_on_trigger: Some(false),
+        order_type: OrderType::Market,
+        leverage: None,
+        side: position.side.opposite(),
+        take_profit: None,
+        stop_loss: None,
+        order_link_id: Some(order_link_id.to_string()),
+    };
+    let ack = market.order(order).await?;
+    info!("Reduce order placed symbol:{} qty:{} order_id:{} status:{:?}", &position.symbol, qty, &ack.order_id, ack.status);
+    Ok(())
+}
+
+/// Split `size` over the take profit levels, numbered from 1. Each level is rounded down to
+/// the lot `step` and the last one takes the remainder, levels left empty are skipped.
+fn ladder(levels: &[TakeProfit], size: Decimal, step: Option<Decimal>) -> Vec<(u32, &TakeProfit, Decimal)> {
+    let hundred = Decimal::ONE_HUNDRED;
+    let mut remaining = size;
+    let mut legs = Vec::new();
+    for (i, level) in levels.iter().enumerate() {
+        let qty = if i + 1 == levels.len() {
+            remaining
         } else {
-            debug!("Switch isolated or change leverage not completed")
+            floor_to_step(size * level.percent / hundred, step).min(remaining)
+        };
+        if qty > Decimal::ZERO {
+            remaining -= qty;
+            legs.push((i as u32 + 1, level, qty.normalize()));
         }
     }
-}
\ No newline at end of file
+    legs
+}
+
+/// `qty` rounded down to the lot `step` when the connector knows it.
+fn floor_to_step(qty: Decimal, step: Option<Decimal>) -> Decimal {
+    match step {
+        Some(step) if step > Decimal::ZERO => (qty / step).floor() * step,
+        _ => qty,
+    }
+}
@@ -0,0 +1,79 @@
+use rust_decimal::Decimal;
+
+use crate::common::environments::{max_leverage, max_notional};
+use crate::exchange::structs::ContractType;
+
+/// How large an entry is.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sizing {
+    /// The whole available balance at the signal's leverage.
+    Balance,
+    /// Fixed order value in USD.
+    Notional(Decimal),
+    /// Fixed quantity in order units, coins or inverse contracts.
+    Qty(Decimal),
+    /// Percent of the available balance used as margin.
+    EquityPercent(Decimal),
+    /// Percent of the available balance lost when the stop loss is hit.
+    Risk(Decimal),
+}
+
+impl std::str::FromStr for Sizing {
+    type Err = String;
+
+    /// `BALANCE`, or a mode and its value such as `NOTIONAL:1000`, `QTY:0.5`, `EQUITY:10` or `RISK:1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mode, value) = match s.split_once(':') {
+            Some((mode, value)) => (mode, Some(value)),
+            None => (s, None),
+        };
+        let mode = mode.trim().to_uppercase();
+        if mode == "BALANCE" && value.is_none() {
+            return Ok(Sizing::Balance);
+        }
+        let value = value
+            .and_then(|value| value.trim().parse::<Decimal>().ok())
+            .filter(|value| *value > Decimal::ZERO)
+            .ok_or_else(|| format!("Sizing {} needs a positive value: {}", mode, s))?;
+        match mode.as_str() {
+            "NOTIONAL" => Ok(Sizing::Notional(value)),
+            "QTY" => Ok(Sizing::Qty(value)),
+            "EQUITY" => Ok(Sizing::EquityPercent(value)),
+            "RISK" => Ok(Sizing::Risk(value)),
+            _ => Err(format!("Unsupported sizing: {}", s)),
+        }
+    }
+}
+
+impl Sizing {
+    /// Order quantity for an entry at `price` with its stop at `stop_loss`. The order value is
+    /// capped at what the balance holds at `leverage` and at `MAX_NOTIONAL`.
+    pub fn order_qty(&self, contract_type: ContractType, balance: Decimal, leverage: i32, price: Decimal, stop_loss: Decimal) -> Decimal {
+        let leverage = if contract_type == ContractType::Spot { 1 } else { leverage };
+        let equity = contract_type.balance_value(balance, price);
+        let margin_notional = equity * Decimal::from(leverage);
+        let notional = match self {
+            Sizing::Balance => margin_notional,
+            Sizing::Notional(notional) => *notional,
+            Sizing::Qty(qty) => contract_type.notional(*qty, price),
+            Sizing::EquityPercent(percent) => margin_notional * percent / Decimal::ONE_HUNDRED,
+            Sizing::Risk(percent) => {
+                // The stop loses distance / price of the order value.
+                let distance = (price - stop_loss).abs();
+                if distance.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    equity * percent / Decimal::ONE_HUNDRED * price / distance
+                }
+            }
+        };
+        let notional = notional.min(margin_notional);
+        let notional = max_notional().map_or(notional, |max| notional.min(max));
+        contract_type.notional_qty(notional, price)
+    }
+}
+
+/// `leverage` capped at `MAX_LEVERAGE`.
+pub fn leverage(leverage: i32) -> i32 {
+    max_leverage().map_or(leverage, |max| leverage.min(max))
+}
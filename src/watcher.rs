@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use log::{error, info, warn};
+use rust_decimal::Decimal;
+
+use crate::common::environments::{breakeven_fee_percent, exit_watch_interval_secs};
+use crate::exchange::error::{ExchangeError, ExchangeResult};
+use crate::exchange::general::MarketApi;
+use crate::exchange::structs::{ExitOptions, OrderSide, OrderStatus, TriggerBy};
+use crate::robot::{Leg, TakeProfit};
+
+/// Where the stop goes once take profit levels fill.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StopRule {
+    /// The stop stays where the signal put it.
+    Fixed,
+    /// Move the stop to the entry (plus fees) after TP1.
+    Breakeven,
+    /// Breakeven after TP1, then to the previous level's price after each further level.
+    Trail,
+}
+
+impl std::str::FromStr for StopRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "FIXED" | "NONE" => Ok(StopRule::Fixed),
+            "BREAKEVEN" => Ok(StopRule::Breakeven),
+            "TRAIL" => Ok(StopRule::Trail),
+            _ => Err(format!("Unsupported stop rule: {}", s)),
+        }
+    }
+}
+
+/// Exits placed for a position, the stop is moved as their take profit levels fill.
+pub struct Exits<'a> {
+    pub signal_id: &'a str,
+    pub symbol: &'a str,
+    pub side: OrderSide,
+    pub entry_price: Decimal,
+    pub stop_rule: StopRule,
+    /// Price the moved stop is triggered by, as the one placed with the entry.
+    pub trigger_by: Option<TriggerBy>,
+    /// Placed take profit levels by number.
+    pub levels: Vec<(u32, TakeProfit)>,
+}
+
+impl Exits<'_> {
+    /// Stop price once take profit level `index` (into `levels`) filled.
+    fn stop_after(&self, index: usize) -> Option<Decimal> {
+        match (self.stop_rule, index) {
+            (StopRule::Fixed, _) => None,
+            (_, 0) => Some(self.breakeven()),
+            (StopRule::Breakeven, _) => None,
+            (StopRule::Trail, _) => Some(self.levels[index - 1].1.price),
+        }
+    }
+
+    /// Entry price moved by the round trip fees, so a stop there closes without a loss.
+    fn breakeven(&self) -> Decimal {
+        let fees = self.entry_price * breakeven_fee_percent() / Decimal::ONE_HUNDRED;
+        match self.side {
+            OrderSide::Long => self.entry_price + fees,
+            OrderSide::Short => self.entry_price - fees,
+        }
+    }
+}
+
+/// Poll the take profit orders of `exits` until the position is closed and move the stop
+/// by its rule as they fill. Only connectors that look orders up by client ID are watched.
+pub async fn watch(market: &dyn MarketApi, exits: Exits<'_>) {
+    let interval = Duration::from_secs(exit_watch_interval_secs());
+    let Exits { signal_id, symbol, .. } = exits;
+    let mut stop_link = Leg::StopLoss.link_id(signal_id);
+    // The last level closes the position, there is nothing to move after it.
+    let mut next = 0;
+    while next + 1 < exits.levels.len() {
+        tokio::time::sleep(interval).await;
+        let (n, level) = &exits.levels[next];
+        let tp_link = Leg::TakeProfit(*n).link_id(signal_id);
+        match market.find_order(symbol, &tp_link).await {
+            Ok(Some(order)) if order.status == OrderStatus::Filled => {
+                info!("Take profit filled symbol:{} link:{} price:{}", symbol, &tp_link, level.price);
+                if let Some(stop) = exits.stop_after(next) {
+                    let link = Leg::MovedStop(*n).link_id(signal_id);
+                    match move_stop(market, &exits, &stop_link, &link, stop).await {
+                        Ok(()) => {
+                            info!("Stop moved symbol:{} price:{} link:{}", symbol, stop, &link);
+                            stop_link = link;
+                        }
+                        Err(e) => error!("Move stop failed symbol:{} price:{} {}", symbol, stop, e),
+                    }
+                }
+                next += 1;
+                continue;
+            }
+            Ok(Some(order)) if !order.status.is_open() => {
+                info!("Take profit {} ended {:?}, stop watching symbol:{}", &tp_link, order.status, symbol);
+                return;
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                warn!("Take profit {} not found, stop watching symbol:{}", &tp_link, symbol);
+                return;
+            }
+            Err(ExchangeError::Unsupported(_)) => return,
+            Err(e) if e.is_retryable() => warn!("Watch exits interrupted symbol:{} {}", symbol, e),
+            Err(e) => {
+                error!("Watch exits failed symbol:{} {}", symbol, e);
+                return;
+            }
+        }
+        if let Ok(false) = market.is_in_position(symbol).await {
+            info!("Position closed, stop watching symbol:{}", symbol);
+            return;
+        }
+    }
+}
+
+/// Replace the stop with one at `stop` for what is left of the position.
+async fn move_stop(market: &dyn MarketApi, exits: &Exits<'_>, stop_link: &str, link: &str, stop: Decimal) -> ExchangeResult<()> {
+    let size = match market.position(exits.symbol).await? {
+        Some(position) if position.size > Decimal::ZERO => position.size,
+        _ => return Ok(()),
+    };
+    // Connectors placing stops as orders keep the previous one, position level stops are replaced in place.
+    if let Some(order) = market.find_order(exits.symbol, stop_link).await? {
+        if order.status.is_open() {
+            market.cancel_order(exits.symbol, &order.order_id).await?;
+        }
+    }
+    market.stop_loss(exits.symbol, Some(size), &exits.side, None, Some(stop), ExitOptions { order_link_id: Some(link), trigger_by: exits.trigger_by }).await
+}